@@ -0,0 +1,290 @@
+//! Benchmarks every day, timing the generator apart from each part, on the real input and on
+//! scaled-up synthetic inputs.
+//!
+//! Save a baseline before a change and compare against it afterwards:
+//!
+//! ```sh
+//! cargo bench --bench days -- --save-baseline before
+//! cargo bench --bench days -- --baseline before
+//! ```
+
+use advent2023::gen::Generator;
+use advent2023::{day01, day02, day03, day04, day05, day06, day07, day08, day09};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::fs;
+use std::hint::black_box;
+
+const SCALES: [usize; 3] = [1, 4, 16];
+
+/// real reads the real input of `day` the way the runner does, if it is there.
+fn real(day: u8) -> Option<String> {
+    let path = format!("{}/input/2023/day{}.txt", env!("CARGO_MANIFEST_DIR"), day);
+
+    fs::read_to_string(path)
+        .ok()
+        .map(|s| s.trim_end_matches('\n').to_string())
+}
+
+/// repeat_lines concatenates `n` copies of the lines of `input`, letting `line` renumber the
+/// i-th line of the result.
+fn repeat_lines(input: &str, n: usize, line: impl Fn(usize, &str) -> String) -> String {
+    let lines = input.lines().collect::<Vec<_>>();
+
+    (0..n * lines.len())
+        .map(|i| line(i, lines[i % lines.len()]))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// renumber replaces the id of `Game 12: ...` or `Card 12: ...` lines with `id`.
+fn renumber(line: &str, id: usize) -> String {
+    let (head, rest) = line.split_once(':').unwrap();
+    let word = head.split_whitespace().next().unwrap();
+
+    format!("{} {}:{}", word, id, rest)
+}
+
+/// tile lays out `n` by `n` copies of the grid in `input`.
+fn tile(input: &str, n: usize) -> String {
+    let rows = input.lines().map(|l| l.repeat(n)).collect::<Vec<_>>();
+
+    (0..n)
+        .flat_map(|_| rows.iter().cloned())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// bench_day times `parse` on its own, then both parts on an already parsed input.
+fn bench_day<P, A, B>(
+    c: &mut Criterion,
+    name: &str,
+    inputs: &[(String, String)],
+    parse: impl Fn(&str) -> P,
+    part1: impl Fn(&P) -> A,
+    part2: impl Fn(&P) -> B,
+) {
+    let mut group = c.benchmark_group(name);
+
+    for (label, input) in inputs {
+        let parsed = parse(input);
+        group.throughput(Throughput::Bytes(input.len() as u64));
+
+        group.bench_with_input(BenchmarkId::new("parse", label), input, |b, i| {
+            b.iter(|| parse(black_box(i)))
+        });
+        group.bench_with_input(BenchmarkId::new("part1", label), &parsed, |b, p| {
+            b.iter(|| part1(black_box(p)))
+        });
+        group.bench_with_input(BenchmarkId::new("part2", label), &parsed, |b, p| {
+            b.iter(|| part2(black_box(p)))
+        });
+    }
+
+    group.finish();
+}
+
+/// labelled labels the real input, if there is one, for bench_day.
+fn labelled(day: u8) -> Vec<(String, String)> {
+    real(day)
+        .map(|input| vec![("real".to_string(), input)])
+        .unwrap_or_default()
+}
+
+/// scaled pairs the real input with copies of it grown by `grow` at every scale.
+fn scaled(day: u8, grow: impl Fn(&str, usize) -> String) -> Vec<(String, String)> {
+    let mut inputs = labelled(day);
+
+    if let Some((_, input)) = inputs.first().cloned() {
+        inputs.extend(
+            SCALES
+                .iter()
+                .skip(1)
+                .map(|&n| (format!("x{}", n), grow(&input, n))),
+        );
+    }
+
+    inputs
+}
+
+/// generated pairs the real input with inputs from the generator at every scale of `base`.
+fn generated(day: u8, base: usize) -> Vec<(String, String)> {
+    let mut inputs = labelled(day);
+    inputs.extend(SCALES.iter().map(|&n| {
+        let input = Generator::new(u64::from(day))
+            .with_size(base * n)
+            .input(day)
+            .unwrap();
+
+        (format!("generated{}", base * n), input)
+    }));
+
+    inputs
+}
+
+fn day1(c: &mut Criterion) {
+    let inputs = scaled(1, |input, n| repeat_lines(input, n, |_, l| l.to_string()));
+
+    bench_day(
+        c,
+        "day1",
+        &inputs,
+        |i| i.to_string(),
+        |i| day01::day1_part1_puzzle(i),
+        |i| day01::day1_part2_puzzle(i),
+    );
+}
+
+/// day1_scanner compares the automaton finding the first and last digit of every line with
+/// finding all of its digits first.
+fn day1_scanner(c: &mut Criterion) {
+    let english = day01::Vocabulary::english();
+    let mut group = c.benchmark_group("day1_scanner");
+
+    for (label, input) in generated(1, 10_000) {
+        let lines = input.lines().collect::<Vec<_>>();
+        group.throughput(Throughput::Bytes(input.len() as u64));
+
+        group.bench_with_input(BenchmarkId::new("scanner", &label), &lines, |b, lines| {
+            b.iter(|| {
+                lines
+                    .iter()
+                    .map(|l| english.calibrate(black_box(l)))
+                    .collect::<Vec<_>>()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("splitter", &label), &lines, |b, lines| {
+            b.iter(|| {
+                lines
+                    .iter()
+                    .map(|l| day01::spelled_digits_by_splitting(black_box(l)))
+                    .collect::<Vec<_>>()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn day2(c: &mut Criterion) {
+    let inputs = scaled(2, |input, n| {
+        repeat_lines(input, n, |i, l| renumber(l, i + 1))
+    });
+
+    bench_day(
+        c,
+        "day2",
+        &inputs,
+        |i| day02::parse_day2(i).unwrap(),
+        day02::day2_part1,
+        day02::day2_part2,
+    );
+}
+
+fn day3(c: &mut Criterion) {
+    let inputs = scaled(3, tile);
+
+    bench_day(
+        c,
+        "day3",
+        &inputs,
+        |i| day03::parse_day3(i).unwrap(),
+        day03::day3_part1,
+        day03::day3_part2,
+    );
+}
+
+fn day4(c: &mut Criterion) {
+    let inputs = scaled(4, |input, n| {
+        repeat_lines(input, n, |i, l| renumber(l, i + 1))
+    });
+
+    bench_day(
+        c,
+        "day4",
+        &inputs,
+        |i| day04::parse_day4(i).unwrap(),
+        |cards| day04::day4_part1(cards),
+        |cards| day04::day4_part2(cards),
+    );
+}
+
+fn day5(c: &mut Criterion) {
+    bench_day(
+        c,
+        "day5",
+        &generated(5, 40),
+        |i| day05::parse_day5(i).unwrap(),
+        day05::day5_part1,
+        day05::day5_part2,
+    );
+}
+
+fn day6(c: &mut Criterion) {
+    // The races do not grow with the input: part 2 concatenates every race into one number.
+    bench_day(
+        c,
+        "day6",
+        &labelled(6),
+        |i| {
+            (
+                day06::parse_day6(i).unwrap(),
+                day06::parse_day6_part2(i).unwrap(),
+            )
+        },
+        |(races, _)| day06::day6_part1(races),
+        |(_, race)| day06::day6_part2(race),
+    );
+}
+
+fn day7(c: &mut Criterion) {
+    let inputs = scaled(7, |input, n| repeat_lines(input, n, |_, l| l.to_string()));
+
+    bench_day(
+        c,
+        "day7",
+        &inputs,
+        |i| day07::parse_day7(i).unwrap(),
+        |hands| day07::day7_part1(hands),
+        |hands| day07::day7_part2(hands),
+    );
+}
+
+fn day8(c: &mut Criterion) {
+    // Node names are three characters long, so the network cannot be grown by copying it.
+    bench_day(
+        c,
+        "day8",
+        &generated(8, 16),
+        |i| day08::parse_day8(i).unwrap(),
+        day08::day8_part1,
+        day08::day8_part2,
+    );
+}
+
+fn day9(c: &mut Criterion) {
+    let inputs = scaled(9, |input, n| repeat_lines(input, n, |_, l| l.to_string()));
+
+    bench_day(
+        c,
+        "day9",
+        &inputs,
+        |i| day09::parse_day9(i).unwrap(),
+        |report| day09::day9_part1(report),
+        |report| day09::day9_part2(report),
+    );
+}
+
+criterion_group!(
+    days,
+    day1,
+    day1_scanner,
+    day2,
+    day3,
+    day4,
+    day5,
+    day6,
+    day7,
+    day8,
+    day9
+);
+criterion_main!(days);
@@ -0,0 +1,97 @@
+//! Benchmarks the solvers that split their work into independent records on large generated
+//! inputs, to compare one core against every core.
+//!
+//! Run it without the `parallel` feature to save a baseline, then with it to see the speedup:
+//!
+//! ```sh
+//! cargo bench --bench parallel -- --save-baseline sequential
+//! cargo bench --bench parallel --features parallel -- --baseline sequential
+//! ```
+
+use advent2023::gen::Generator;
+use advent2023::{day01, day02, day05, day08, day09};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use std::hint::black_box;
+
+/// generated returns the input of `day` from the generator at `size`.
+fn generated(day: u8, size: usize) -> String {
+    Generator::new(u64::from(day))
+        .with_size(size)
+        .input(day)
+        .unwrap()
+}
+
+/// bench parses `input` once, then times `solve` on it.
+fn bench<P, A>(
+    c: &mut Criterion,
+    name: &str,
+    input: &str,
+    parse: impl Fn(&str) -> P,
+    solve: impl Fn(&P) -> A,
+) {
+    let parsed = parse(input);
+
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_function("solve", |b| b.iter(|| solve(black_box(&parsed))));
+    group.finish();
+}
+
+fn parallel(c: &mut Criterion) {
+    let input = generated(1, 200_000);
+    let english = day01::Vocabulary::english();
+    bench(
+        c,
+        "day1_part2",
+        &input,
+        |i| i.to_string(),
+        |i| day01::day1_part2(i, &english, day01::Digitless::Error),
+    );
+
+    let input = generated(2, 200_000);
+    bench(
+        c,
+        "day2_part2",
+        &input,
+        |i| day02::parse_day2(i).unwrap(),
+        day02::day2_part2,
+    );
+
+    let input = generated(5, 400);
+    bench(
+        c,
+        "day5_part1",
+        &input,
+        |i| day05::parse_day5(i).unwrap(),
+        day05::day5_part1,
+    );
+
+    let input = generated(8, 300);
+    bench(
+        c,
+        "day8_part2",
+        &input,
+        |i| day08::parse_day8(i).unwrap(),
+        day08::day8_part2,
+    );
+
+    let input = generated(9, 50_000);
+    let report = day09::parse_day9(&input).unwrap();
+    bench(
+        c,
+        "day9_part1",
+        &input,
+        |_| &report,
+        |r| day09::day9_part1(r),
+    );
+    bench(
+        c,
+        "day9_part2",
+        &input,
+        |_| &report,
+        |r| day09::day9_part2(r),
+    );
+}
+
+criterion_group!(benches, parallel);
+criterion_main!(benches);
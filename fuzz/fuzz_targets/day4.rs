@@ -0,0 +1,11 @@
+#![no_main]
+
+use advent2023::solutions::solutions;
+use libfuzzer_sys::fuzz_target;
+
+// Every input must either be rejected or answered, never panic.
+fuzz_target!(|input: &str| {
+    for solution in solutions().iter().filter(|s| s.day == 4) {
+        let _ = solution.run(input);
+    }
+});
@@ -0,0 +1,264 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Answers holds the known correct answer of every day and part, as kept in
+/// `answers/<year>.toml`:
+///
+/// ```toml
+/// [day1]
+/// part1 = "53386"
+/// part2 = "53312"
+/// ```
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub struct Answers {
+    entries: BTreeMap<(u8, u8), String>,
+}
+
+#[derive(Debug)]
+pub enum AnswersError {
+    Io(PathBuf, io::Error),
+    Toml(toml::de::Error),
+    /// A table or key that is not `dayN` / `partN`, or a value that is not a string or integer.
+    Malformed(String),
+}
+
+impl fmt::Display for AnswersError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnswersError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            AnswersError::Toml(e) => write!(f, "{}", e),
+            AnswersError::Malformed(what) => write!(f, "malformed answers file: {}", what),
+        }
+    }
+}
+
+impl Error for AnswersError {}
+
+impl Answers {
+    pub fn path(year: u16) -> PathBuf {
+        PathBuf::from(format!("answers/{}.toml", year))
+    }
+
+    /// load reads the answers at `path`, treating a missing file as having no answers yet.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, AnswersError> {
+        let path = path.as_ref();
+
+        match fs::read_to_string(path) {
+            Ok(text) => text.parse(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(AnswersError::Io(path.to_path_buf(), e)),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), AnswersError> {
+        let path = path.as_ref();
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| AnswersError::Io(dir.to_path_buf(), e))?;
+        }
+        fs::write(path, self.to_string()).map_err(|e| AnswersError::Io(path.to_path_buf(), e))
+    }
+
+    pub fn get(&self, day: u8, part: u8) -> Option<&str> {
+        self.entries.get(&(day, part)).map(String::as_str)
+    }
+
+    pub fn insert(&mut self, day: u8, part: u8, answer: impl Into<String>) {
+        self.entries.insert((day, part), answer.into());
+    }
+
+    pub fn check(&self, day: u8, part: u8, answer: &str) -> Status {
+        match self.get(day, part) {
+            Some(expected) if expected == answer => Status::Pass,
+            Some(expected) => Status::Fail {
+                expected: expected.to_string(),
+            },
+            None => Status::Missing,
+        }
+    }
+}
+
+impl std::str::FromStr for Answers {
+    type Err = AnswersError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tables: BTreeMap<String, BTreeMap<String, toml::Value>> =
+            toml::from_str(s).map_err(AnswersError::Toml)?;
+
+        let number = |key: &str, prefix: &str| {
+            key.strip_prefix(prefix)
+                .and_then(|n| n.parse::<u8>().ok())
+                .ok_or_else(|| AnswersError::Malformed(format!("unexpected key `{}`", key)))
+        };
+
+        let mut answers = Answers::default();
+        for (day_key, parts) in tables {
+            let day = number(&day_key, "day")?;
+
+            for (part_key, value) in parts {
+                let part = number(&part_key, "part")?;
+                let answer = match value {
+                    toml::Value::String(s) => s,
+                    toml::Value::Integer(n) => n.to_string(),
+                    other => {
+                        return Err(AnswersError::Malformed(format!(
+                            "{}.{} is {}",
+                            day_key,
+                            part_key,
+                            other.type_str()
+                        )))
+                    }
+                };
+
+                answers.insert(day, part, answer);
+            }
+        }
+
+        Ok(answers)
+    }
+}
+
+impl fmt::Display for Answers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut previous = None;
+
+        for (&(day, part), answer) in self.entries.iter() {
+            if previous != Some(day) {
+                if previous.is_some() {
+                    writeln!(f)?;
+                }
+                writeln!(f, "[day{}]", day)?;
+                previous = Some(day);
+            }
+
+            writeln!(f, "part{} = {:?}", part, answer)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum Status {
+    Pass,
+    Fail {
+        expected: String,
+    },
+    /// There is no recorded answer to compare against.
+    Missing,
+    /// The solution failed to produce an answer at all.
+    Error(String),
+    /// The puzzle input could not be loaded, so the solution never ran.
+    NoInput(String),
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Pass => write!(f, "pass"),
+            Status::Fail { .. } => write!(f, "FAIL"),
+            Status::Missing => write!(f, "missing"),
+            Status::Error(_) => write!(f, "ERROR"),
+            Status::NoInput(_) => write!(f, "missing input"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solutions::{solutions, YEAR};
+
+    #[test]
+    fn test_parse_and_display() {
+        let text = [
+            "[day1]",
+            "part1 = \"142\"",
+            "part2 = 281",
+            "",
+            "[day10]",
+            "part1 = \"-3\"",
+        ]
+        .join("\n");
+        let answers: Answers = text.parse().unwrap();
+
+        assert_eq!(answers.get(1, 1), Some("142"));
+        assert_eq!(answers.get(1, 2), Some("281"));
+        assert_eq!(answers.get(10, 1), Some("-3"));
+        assert_eq!(answers.get(2, 1), None);
+        assert_eq!(
+            answers.to_string(),
+            [
+                "[day1]",
+                "part1 = \"142\"",
+                "part2 = \"281\"",
+                "",
+                "[day10]",
+                "part1 = \"-3\"",
+                ""
+            ]
+            .join("\n")
+        );
+        assert_eq!(answers.to_string().parse::<Answers>().unwrap(), answers);
+    }
+
+    #[test]
+    fn test_parse_malformed() {
+        assert!(matches!(
+            "[dayone]\npart1 = \"1\"".parse::<Answers>(),
+            Err(AnswersError::Malformed(_))
+        ));
+        assert!(matches!(
+            "[day1]\npart1 = 1.5".parse::<Answers>(),
+            Err(AnswersError::Malformed(_))
+        ));
+        assert!(matches!(
+            "[day1".parse::<Answers>(),
+            Err(AnswersError::Toml(_))
+        ));
+    }
+
+    #[test]
+    fn test_check() {
+        let mut answers = Answers::default();
+        answers.insert(1, 1, "142");
+
+        assert_eq!(answers.check(1, 1, "142"), Status::Pass);
+        assert_eq!(
+            answers.check(1, 1, "143"),
+            Status::Fail {
+                expected: "142".to_string()
+            }
+        );
+        assert_eq!(answers.check(1, 2, "281"), Status::Missing);
+    }
+
+    /// test_recorded_answers runs every solution whose real input is present and compares it
+    /// with the recorded answer.
+    #[test]
+    fn test_recorded_answers() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let answers = Answers::load(root.join(Answers::path(YEAR))).unwrap();
+
+        for solution in solutions() {
+            let path = root.join(format!("input/{}/day{}.txt", YEAR, solution.day));
+            let (Some(expected), Ok(input)) = (
+                answers.get(solution.day, solution.part),
+                fs::read_to_string(path),
+            ) else {
+                continue;
+            };
+
+            let output = solution.run(&input).unwrap();
+            assert_eq!(
+                output.answer, expected,
+                "day {} part {}",
+                solution.day, solution.part
+            );
+        }
+    }
+}
@@ -1,58 +1,687 @@
-use aoc_runner_derive::aoc;
+use crate::par;
+use crate::stream::{for_each_line, StreamError};
 use itertools::Itertools;
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::{fmt, fs, iter};
 
-#[aoc(day1, part1)]
-fn day1_part1(input: &str) -> usize {
-    input
+/// NoDigit reports a line without any digit to calibrate with, counting lines from 1.
+#[derive(Debug, Eq, PartialEq)]
+pub struct NoDigit {
+    pub line: usize,
+}
+
+impl fmt::Display for NoDigit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {} has no digit", self.line)
+    }
+}
+
+impl Error for NoDigit {}
+
+/// Digitless decides what becomes of a line without any digit.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Digitless {
+    /// Stop with a NoDigit error naming the line.
+    #[default]
+    Error,
+    /// Leave the line out.
+    Skip,
+    /// Calibrate the line to 0.
+    Zero,
+}
+
+impl Digitless {
+    /// settle returns the calibration of `line`, or None when the line is left out.
+    fn settle(self, line: usize, value: Option<usize>) -> Result<Option<usize>, NoDigit> {
+        match (value, self) {
+            (Some(value), _) => Ok(Some(value)),
+            (None, Digitless::Error) => Err(NoDigit { line }),
+            (None, Digitless::Skip) => Ok(None),
+            (None, Digitless::Zero) => Ok(Some(0)),
+        }
+    }
+}
+
+pub fn day1_part1_puzzle(input: &str) -> Result<usize, NoDigit> {
+    day1_part1(input, Digitless::Error)
+}
+
+pub fn day1_part2_puzzle(input: &str) -> Result<usize, NoDigit> {
+    day1_part2(input, &Vocabulary::english(), Digitless::Error)
+}
+
+pub fn day1_part1(input: &str, digitless: Digitless) -> Result<usize, NoDigit> {
+    Ok(calibrations(input, digits, digitless)?
+        .into_iter()
+        .map(|(_, value)| value)
+        .sum())
+}
+
+/// day1_part2 reads the digits of every line whether they are written as digits or spelled out
+/// in `vocabulary`.
+pub fn day1_part2(
+    input: &str,
+    vocabulary: &Vocabulary,
+    digitless: Digitless,
+) -> Result<usize, NoDigit> {
+    Ok(calibrations(input, |l| vocabulary.calibrate(l), digitless)?
+        .into_iter()
+        .map(|(_, value)| value)
+        .sum())
+}
+
+/// calibrations pairs the number of every line, counting from 1, with its calibration value
+/// read by `value`. Trailing blank lines are not lines, the same as for the streams.
+pub fn calibrations(
+    input: &str,
+    value: impl Fn(&str) -> Option<usize> + Sync + Send,
+    digitless: Digitless,
+) -> Result<Vec<(usize, usize)>, NoDigit> {
+    let lines = input
+        .trim_end_matches(['\n', '\r'])
         .lines()
-        .map(|l| {
-            l.chars()
-                .find_or_first(|c| c.is_numeric())
-                .unwrap()
-                .to_string()
-                + &l.chars()
-                    .rev()
-                    .find_or_first(|c| c.is_numeric())
-                    .unwrap()
-                    .to_string()
+        .collect::<Vec<_>>();
+
+    par::map(&lines, |l| value(l))
+        .into_iter()
+        .zip(1..)
+        .filter_map(|(value, line)| {
+            let settled = digitless.settle(line, value);
+            settled.map(|v| v.map(|v| (line, v))).transpose()
         })
-        .map(|s| s.parse::<usize>().unwrap())
-        .sum()
+        .collect()
 }
 
-#[aoc(day1, part2)]
-fn day1_part2(input: &str) -> usize {
-    input
-        .lines()
-        .map(splitter)
-        .map(|v| v.first().unwrap() * 10 + v.last().unwrap())
-        .sum()
+/// day1_part1_stream is day1_part1 reading one line at a time.
+pub fn day1_part1_stream(input: impl BufRead, digitless: Digitless) -> Result<usize, StreamError> {
+    sum_stream(input, digits, digitless)
 }
 
-fn splitter(s: &str) -> Vec<usize> {
-    const NAMES: [(usize, &str); 20] = [
-        (0, "0"),
-        (0, "zero"),
-        (1, "1"),
-        (1, "one"),
-        (2, "2"),
-        (2, "two"),
-        (3, "3"),
-        (3, "three"),
-        (4, "4"),
-        (4, "four"),
-        (5, "5"),
-        (5, "five"),
-        (6, "6"),
-        (6, "six"),
-        (7, "7"),
-        (7, "seven"),
-        (8, "8"),
-        (8, "eight"),
-        (9, "9"),
-        (9, "nine"),
-    ];
+/// day1_part2_stream is day1_part2 reading one line at a time.
+pub fn day1_part2_stream(
+    input: impl BufRead,
+    vocabulary: &Vocabulary,
+    digitless: Digitless,
+) -> Result<usize, StreamError> {
+    sum_stream(input, |l| vocabulary.calibrate(l), digitless)
+}
+
+fn sum_stream(
+    input: impl BufRead,
+    value: impl Fn(&str) -> Option<usize>,
+    digitless: Digitless,
+) -> Result<usize, StreamError> {
+    let mut sum = 0;
+    for_each_line(input, |line, l| {
+        let value = digitless
+            .settle(line, value(l))
+            .map_err(|e| StreamError::Solve(Box::new(e)))?;
+        sum += value.unwrap_or(0);
+        Ok(())
+    })?;
+
+    Ok(sum)
+}
+
+/// digits combines the first and last digit of a line.
+fn digits(l: &str) -> Option<usize> {
+    let mut digits = l.chars().filter_map(|c| c.to_digit(10));
+    let first = digits.next()?;
+    let last = digits.next_back().unwrap_or(first);
+
+    Some((first * 10 + last) as usize)
+}
+
+/// Vocabulary lists the words that spell out numbers, on top of the digits themselves. A word
+/// may stand for a number of several digits, such as "twentyone" for 21, and then reads as
+/// those digits in turn. Where words overlap the longest one wins, so with "twenty" and
+/// "twentyone" both listed, "twentyone" starts with a 2 and ends with a 1.
+#[derive(Clone, Debug, Default)]
+pub struct Vocabulary {
+    words: Vec<(String, usize)>,
+    ignore_case: bool,
+    /// The scanners reading lines from either end, built on first use.
+    scanners: OnceLock<(Scanner, Scanner)>,
+}
+
+#[derive(Debug)]
+pub enum VocabularyError {
+    Io(PathBuf, io::Error),
+    Toml(toml::de::Error),
+    /// A key other than `ignore_case` and `words`, or a word that is not a number.
+    Malformed(String),
+}
+
+impl fmt::Display for VocabularyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VocabularyError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            VocabularyError::Toml(e) => write!(f, "{}", e),
+            VocabularyError::Malformed(what) => write!(f, "malformed vocabulary: {}", what),
+        }
+    }
+}
+
+impl Error for VocabularyError {}
+
+impl Vocabulary {
+    /// new spells out each value by its word. A word listed twice keeps its last value.
+    pub fn new<S: Into<String>>(words: impl IntoIterator<Item = (S, usize)>) -> Self {
+        Self {
+            words: words.into_iter().map(|(w, v)| (w.into(), v)).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// english is the vocabulary of the puzzle, from "zero" to "nine".
+    pub fn english() -> Self {
+        Self::new(
+            NAMES
+                .iter()
+                .filter(|(_, n)| n.len() > 1)
+                .map(|&(v, n)| (n, v)),
+        )
+    }
+
+    pub fn french() -> Self {
+        Self::numbered([
+            "zéro", "un", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf",
+        ])
+    }
+
+    pub fn german() -> Self {
+        Self::numbered([
+            "null", "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun",
+        ])
+    }
+
+    pub fn spanish() -> Self {
+        Self::numbered([
+            "cero", "uno", "dos", "tres", "cuatro", "cinco", "seis", "siete", "ocho", "nueve",
+        ])
+    }
+
+    /// roman spells 1 to 39 in lowercase Roman numerals, so "xiv" reads as 1 then 4.
+    pub fn roman() -> Self {
+        const ONES: [&str; 10] = ["", "i", "ii", "iii", "iv", "v", "vi", "vii", "viii", "ix"];
+
+        Self::new((1..40).map(|n| (format!("{}{}", "x".repeat(n / 10), ONES[n % 10]), n)))
+    }
+
+    /// numbered spells every digit by its index in `words`.
+    fn numbered(words: [&str; 10]) -> Self {
+        Self::new(words.into_iter().zip(0..))
+    }
+
+    /// load reads a vocabulary written the way FromStr expects.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, VocabularyError> {
+        let path = path.as_ref();
+
+        fs::read_to_string(path)
+            .map_err(|e| VocabularyError::Io(path.to_path_buf(), e))?
+            .parse()
+    }
+
+    /// with_word adds `word`, or gives it `value` instead if the vocabulary has it already.
+    pub fn with_word(mut self, word: impl Into<String>, value: usize) -> Self {
+        let word = word.into();
+        match self.words.iter_mut().find(|(w, _)| *w == word) {
+            Some((_, v)) => *v = value,
+            None => self.words.push((word, value)),
+        }
+        self.scanners = OnceLock::new();
+        self
+    }
+
+    /// with_ignore_case makes words match whatever their case, e.g. "Eight" and "EIGHT".
+    pub fn with_ignore_case(self, ignore_case: bool) -> Self {
+        Self {
+            ignore_case,
+            scanners: OnceLock::new(),
+            ..self
+        }
+    }
+
+    /// calibrate combines the first and last digit of a line, spelled out or not.
+    pub fn calibrate(&self, l: &str) -> Option<usize> {
+        let (forward, backward) = self.scanners.get_or_init(|| self.scanners_for_words());
+        let l = self.normalize(l);
+
+        let first = forward.leftmost(l.bytes())?;
+        let last = backward.leftmost(l.bytes().rev())?;
+
+        Some(leading_digit(first) * 10 + last % 10)
+    }
+
+    /// explain shows how every line of `input` is calibrated. Explaining with the empty
+    /// vocabulary, `Vocabulary::default()`, shows how part 1 reads the lines. Trailing blank
+    /// lines are left out, the same as for calibrations.
+    pub fn explain(&self, input: &str) -> Explanation {
+        let words = self.words();
+
+        let lines = input
+            .trim_end_matches(['\n', '\r'])
+            .lines()
+            .zip(1..)
+            .map(|(l, line)| {
+                let (text, origins) = self.fold(l);
+                // Matches as (offset, length, value) in the text the scanners read.
+                let found = text
+                    .char_indices()
+                    .flat_map(|(offset, _)| {
+                        let rest = &text[offset..];
+                        words
+                            .iter()
+                            .filter(move |(_, w)| rest.starts_with(w.as_str()))
+                            .map(move |(value, w)| (offset, w.len(), *value))
+                    })
+                    .sorted_by_key(|&(offset, len, _)| (offset, Reverse(len)))
+                    .collect::<Vec<_>>();
+
+                // The same words the scanners settle on: the first to start and the last to
+                // end, the longest of them on a tie.
+                let first = found.first().map(|&(_, _, value)| leading_digit(value));
+                let last = found
+                    .iter()
+                    .min_by_key(|&&(offset, len, _)| (Reverse(offset + len), Reverse(len)))
+                    .map(|&(_, _, value)| value % 10);
+
+                let tokens = found
+                    .into_iter()
+                    .map(|(offset, len, value)| {
+                        let start = origins[offset].0;
+                        let end = if len == 0 {
+                            start
+                        } else {
+                            origins[offset + len - 1].1
+                        };
+                        Token {
+                            offset: start,
+                            text: l[start..end].to_string(),
+                            value,
+                        }
+                    })
+                    .collect();
+
+                LineExplanation {
+                    line,
+                    tokens,
+                    first,
+                    last,
+                    value: first.zip(last).map(|(first, last)| first * 10 + last),
+                }
+            })
+            .collect();
+
+        Explanation { lines }
+    }
+
+    /// normalize lowers the case of `l` when case is ignored, character by character.
+    fn normalize<'a>(&self, l: &'a str) -> Cow<'a, str> {
+        match self.ignore_case {
+            true => Cow::Owned(l.chars().flat_map(char::to_lowercase).collect()),
+            false => Cow::Borrowed(l),
+        }
+    }
+
+    /// fold is normalize along with where every byte of the result comes from: the start and
+    /// end in `l` of the character it was lowered from. Lowering can change how many bytes a
+    /// character takes, e.g. "İ" becomes "i̇".
+    fn fold<'a>(&self, l: &'a str) -> (Cow<'a, str>, Vec<(usize, usize)>) {
+        let mut origins = vec![];
+        for (start, c) in l.char_indices() {
+            let lowered = match self.ignore_case {
+                true => c.to_lowercase().map(char::len_utf8).sum(),
+                false => c.len_utf8(),
+            };
+            origins.extend(iter::repeat_n((start, start + c.len_utf8()), lowered));
+        }
+
+        (self.normalize(l), origins)
+    }
+
+    /// words lists the digits and the words, with the case the scanners match them in. A word
+    /// spelled the same as an earlier one, digits included, takes its place.
+    fn words(&self) -> Vec<(usize, String)> {
+        let spelled = self.words.iter().map(|(w, v)| match self.ignore_case {
+            true => (*v, w.to_lowercase()),
+            false => (*v, w.clone()),
+        });
+
+        let mut words: Vec<(usize, String)> = vec![];
+        for (value, word) in (0..10).map(|d| (d, d.to_string())).chain(spelled) {
+            match words.iter_mut().find(|(_, w)| *w == word) {
+                Some((v, _)) => *v = value,
+                None => words.push((value, word)),
+            }
+        }
+
+        words
+    }
+
+    fn scanners_for_words(&self) -> (Scanner, Scanner) {
+        let words = self.words();
+        // The backward scanner reads the line byte by byte from its end, so its words are
+        // reversed byte by byte too.
+        let reversed = words
+            .iter()
+            .map(|(v, w)| (*v, w.bytes().rev().collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+
+        (
+            Scanner::new(words.iter().map(|(v, w)| (*v, w.as_bytes()))),
+            Scanner::new(reversed.iter().map(|(v, w)| (*v, w.as_slice()))),
+        )
+    }
+}
+
+/// leading_digit returns the first digit of `n` written out.
+fn leading_digit(mut n: usize) -> usize {
+    while n >= 10 {
+        n /= 10;
+    }
+
+    n
+}
+
+/// Token is a digit or word found in a line, `offset` bytes in, as written in the line.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Token {
+    pub offset: usize,
+    pub text: String,
+    pub value: usize,
+}
+
+/// LineExplanation is how a line was calibrated, counting lines from 1.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LineExplanation {
+    pub line: usize,
+    /// Every token, overlapping ones included, ordered by offset.
+    pub tokens: Vec<Token>,
+    pub first: Option<usize>,
+    pub last: Option<usize>,
+    pub value: Option<usize>,
+}
+
+/// Explanation renders as a table through Display, and as JSON through to_json.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Explanation {
+    pub lines: Vec<LineExplanation>,
+}
+
+impl Explanation {
+    pub fn to_json(&self) -> String {
+        let number = |n: Option<usize>| n.map_or("null".to_string(), |n| n.to_string());
+
+        let lines = self
+            .lines
+            .iter()
+            .map(|l| {
+                let tokens = l
+                    .tokens
+                    .iter()
+                    .map(|t| {
+                        format!(
+                            r#"{{"offset":{},"text":{},"value":{}}}"#,
+                            t.offset,
+                            json_string(&t.text),
+                            t.value
+                        )
+                    })
+                    .join(",");
+
+                format!(
+                    r#"{{"line":{},"tokens":[{}],"first":{},"last":{},"value":{}}}"#,
+                    l.line,
+                    tokens,
+                    number(l.first),
+                    number(l.last),
+                    number(l.value)
+                )
+            })
+            .join(",");
+
+        format!(r#"{{"lines":[{}]}}"#, lines)
+    }
+}
+
+/// json_string quotes `s` as a JSON string.
+fn json_string(s: &str) -> String {
+    let mut quoted = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+
+    quoted
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let number = |n: Option<usize>| n.map_or("-".to_string(), |n| n.to_string());
 
+        let header = ["Line", "First", "Last", "Value", "Tokens"].map(String::from);
+        let mut table = vec![header];
+        for l in self.lines.iter() {
+            table.push([
+                l.line.to_string(),
+                number(l.first),
+                number(l.last),
+                number(l.value),
+                l.tokens
+                    .iter()
+                    .map(|t| format!("{}:{}", t.offset, t.text))
+                    .join(" "),
+            ]);
+        }
+
+        let widths = (0..5)
+            .map(|c| {
+                table
+                    .iter()
+                    .map(|row| row[c].chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect::<Vec<_>>();
+        for row in table.iter() {
+            let cells = row
+                .iter()
+                .zip(widths.iter())
+                .map(|(cell, &width)| format!("{:<width$}", cell))
+                .collect::<Vec<_>>();
+
+            writeln!(f, "{}", cells.join("  ").trim_end())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A vocabulary file lists its words with the numbers they stand for:
+///
+/// ```toml
+/// ignore_case = true
+///
+/// [words]
+/// un = 1
+/// vingt-et-un = 21
+/// ```
+impl FromStr for Vocabulary {
+    type Err = VocabularyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let table = toml::from_str::<toml::Table>(s).map_err(VocabularyError::Toml)?;
+
+        let mut vocabulary = Vocabulary::default();
+        for (key, value) in table {
+            match (key.as_str(), value) {
+                ("ignore_case", toml::Value::Boolean(b)) => {
+                    vocabulary = vocabulary.with_ignore_case(b)
+                }
+                ("words", toml::Value::Table(words)) => {
+                    for (word, value) in words {
+                        let value = match value {
+                            toml::Value::Integer(n) => usize::try_from(n).ok(),
+                            _ => None,
+                        };
+                        let Some(value) = value.filter(|_| !word.is_empty()) else {
+                            return Err(VocabularyError::Malformed(format!(
+                                "words.{} is not a number of zero or more",
+                                word
+                            )));
+                        };
+
+                        vocabulary = vocabulary.with_word(word, value);
+                    }
+                }
+                (key, value) => {
+                    return Err(VocabularyError::Malformed(format!(
+                        "unexpected {} `{}`",
+                        value.type_str(),
+                        key
+                    )))
+                }
+            }
+        }
+
+        Ok(vocabulary)
+    }
+}
+
+/// spelled_digits_by_splitting is `Vocabulary::english().calibrate` finding every digit of the
+/// line first. It is kept to check and benchmark the scanner against.
+pub fn spelled_digits_by_splitting(l: &str) -> Option<usize> {
+    let v = splitter(l);
+
+    Some(v.first()? * 10 + v.last()?)
+}
+
+/// Scanner is an Aho–Corasick automaton over a set of words, each standing for a value.
+#[derive(Clone, Debug)]
+struct Scanner {
+    /// next[state][byte] is the state reached after reading `byte`, fail links included.
+    next: Vec<[usize; 256]>,
+    /// matched[state] is the length and value of the longest word ending at `state`.
+    matched: Vec<Option<(usize, usize)>>,
+    longest: usize,
+}
+
+impl Scanner {
+    fn new<'a>(words: impl Iterator<Item = (usize, &'a [u8])>) -> Self {
+        let mut next = vec![[0; 256]];
+        let mut matched = vec![None];
+        let mut longest = 0;
+
+        // The trie, where 0 stands for a missing edge until the fail links fill it in.
+        for (value, word) in words {
+            let mut state = 0;
+            for &b in word {
+                if next[state][b as usize] == 0 {
+                    next.push([0; 256]);
+                    matched.push(None);
+                    next[state][b as usize] = next.len() - 1;
+                }
+                state = next[state][b as usize];
+            }
+
+            if matched[state].is_none() {
+                matched[state] = Some((word.len(), value));
+            }
+            longest = longest.max(word.len());
+        }
+
+        // Breadth first, every missing edge follows the fail link of its state, which is
+        // always closer to the root and so already complete.
+        let mut fail = vec![0; next.len()];
+        let mut queue = next[0]
+            .iter()
+            .copied()
+            .filter(|&s| s != 0)
+            .collect::<VecDeque<_>>();
+        while let Some(state) = queue.pop_front() {
+            if matched[state].is_none() {
+                matched[state] = matched[fail[state]];
+            }
+
+            let fallback = next[fail[state]];
+            for (child, &to) in next[state].iter_mut().zip(fallback.iter()) {
+                if *child == 0 {
+                    *child = to;
+                } else {
+                    fail[*child] = to;
+                    queue.push_back(*child);
+                }
+            }
+        }
+
+        Self {
+            next,
+            matched,
+            longest,
+        }
+    }
+
+    /// leftmost returns the value of the word starting first in `bytes`, preferring the longest
+    /// of those starting together. It stops reading as soon as no earlier word can turn up.
+    fn leftmost(&self, bytes: impl Iterator<Item = u8>) -> Option<usize> {
+        let mut state = 0;
+        let mut best: Option<(usize, usize, usize)> = None;
+
+        for (i, b) in bytes.enumerate() {
+            if best.is_some_and(|(start, _, _)| i >= start + self.longest) {
+                break;
+            }
+
+            state = self.next[state][b as usize];
+            if let Some((len, value)) = self.matched[state] {
+                let start = i + 1 - len;
+                if best.is_none_or(|(s, l, _)| start < s || (start == s && len > l)) {
+                    best = Some((start, len, value));
+                }
+            }
+        }
+
+        best.map(|(_, _, value)| value)
+    }
+}
+
+const NAMES: [(usize, &str); 20] = [
+    (0, "0"),
+    (0, "zero"),
+    (1, "1"),
+    (1, "one"),
+    (2, "2"),
+    (2, "two"),
+    (3, "3"),
+    (3, "three"),
+    (4, "4"),
+    (4, "four"),
+    (5, "5"),
+    (5, "five"),
+    (6, "6"),
+    (6, "six"),
+    (7, "7"),
+    (7, "seven"),
+    (8, "8"),
+    (8, "eight"),
+    (9, "9"),
+    (9, "nine"),
+];
+
+fn splitter(s: &str) -> Vec<usize> {
     NAMES
         .iter()
         .flat_map(|(i, n)| s.match_indices(n).map(move |(idx, _)| (idx, i)))
@@ -64,11 +693,16 @@ fn splitter(s: &str) -> Vec<usize> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
     fn test_part1() {
         let input = ["1abc2", "pqr3stu8vwx", "a1b2c3d4e5f", "treb7uchet"].join("\n");
-        assert_eq!(day1_part1(&input), 142);
+        assert_eq!(day1_part1_puzzle(&input), Ok(142));
+        assert_eq!(
+            day1_part1_stream(input.as_bytes(), Digitless::Error).unwrap(),
+            142
+        );
     }
 
     #[test]
@@ -83,6 +717,342 @@ mod tests {
             "7pqrstsixteen",
         ]
         .join("\n");
-        assert_eq!(day1_part2(&input), 281);
+        assert_eq!(day1_part2_puzzle(&input), Ok(281));
+        assert_eq!(
+            day1_part2_stream(input.as_bytes(), &Vocabulary::english(), Digitless::Error).unwrap(),
+            281
+        );
+    }
+
+    #[test]
+    fn test_no_digit() {
+        let input = ["1abc2", "pqrstu", "7"].join("\n");
+
+        assert_eq!(day1_part1_puzzle(&input), Err(NoDigit { line: 2 }));
+        assert_eq!(day1_part2_puzzle(&input), Err(NoDigit { line: 2 }));
+        assert_eq!(day1_part2_puzzle("one\nx½y"), Err(NoDigit { line: 2 }));
+        assert!(matches!(
+            day1_part1_stream(input.as_bytes(), Digitless::Error),
+            Err(StreamError::Solve(e)) if e.to_string() == "line 2 has no digit"
+        ));
+    }
+
+    #[test]
+    fn test_scanner() {
+        let scanner = Scanner::new(
+            [(1, "i"), (3, "iii"), (4, "iv"), (5, "v")]
+                .map(|(v, w)| (v, w.as_bytes()))
+                .into_iter(),
+        );
+
+        assert_eq!(scanner.leftmost("xiv".bytes()), Some(4));
+        assert_eq!(scanner.leftmost("xviii".bytes()), Some(5));
+        assert_eq!(scanner.leftmost("iiii".bytes()), Some(3));
+        assert_eq!(scanner.leftmost("x".bytes()), None);
+    }
+
+    #[test]
+    fn test_spelled_digits_overlapping() {
+        let calibrate = |l| Vocabulary::english().calibrate(l);
+
+        assert_eq!(calibrate("eightwo"), Some(82));
+        assert_eq!(calibrate("xtwone3fourx"), Some(24));
+        assert_eq!(calibrate("oneight"), Some(18));
+        assert_eq!(calibrate("seven"), Some(77));
+        assert_eq!(calibrate("ninine"), Some(99));
+        assert_eq!(calibrate("twelve"), None);
+        assert_eq!(calibrate("abc"), None);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_scanner_matches_splitter(l in "(one|two|three|four|five|six|seven|eight|nine|zero|[0-9]|[a-z]|ü){0,12}") {
+            prop_assert_eq!(Vocabulary::english().calibrate(&l), spelled_digits_by_splitting(&l));
+        }
+    }
+
+    #[test]
+    fn test_languages() {
+        let french = Vocabulary::french();
+        assert_eq!(french.calibrate("xdeuxyzneufz"), Some(29));
+        assert_eq!(french.calibrate("zéro7"), Some(7));
+        assert_eq!(french.calibrate("cinquante"), Some(55));
+
+        let german = Vocabulary::german();
+        assert_eq!(german.calibrate("einszweifünf"), Some(15));
+        assert_eq!(german.calibrate("siebenundacht"), Some(78));
+
+        let spanish = Vocabulary::spanish();
+        assert_eq!(spanish.calibrate("unodostres"), Some(13));
+        assert_eq!(spanish.calibrate("seisiete"), Some(67));
+
+        let roman = Vocabulary::roman();
+        assert_eq!(roman.calibrate("xiv"), Some(14));
+        assert_eq!(roman.calibrate("vaiii"), Some(53));
+        assert_eq!(roman.calibrate("xxxix"), Some(39));
+        assert_eq!(roman.calibrate("abc"), None);
+    }
+
+    #[test]
+    fn test_ignore_case() {
+        let english = Vocabulary::english();
+        assert_eq!(english.calibrate("EightTWO"), None);
+
+        let english = english.with_ignore_case(true);
+        assert_eq!(english.calibrate("EightTWO"), Some(82));
+        assert_eq!(
+            Vocabulary::german()
+                .with_ignore_case(true)
+                .calibrate("FÜNFx"),
+            Some(55)
+        );
+    }
+
+    #[test]
+    fn test_composite_words() {
+        let tens = Vocabulary::english()
+            .with_word("twenty", 20)
+            .with_word("twentyone", 21);
+
+        assert_eq!(tens.calibrate("twentyone"), Some(21));
+        assert_eq!(tens.calibrate("twentyx"), Some(20));
+        assert_eq!(tens.calibrate("3twenty"), Some(30));
+        assert_eq!(
+            day1_part2("twentyone\nsix", &tens, Digitless::Error),
+            Ok(21 + 66)
+        );
+    }
+
+    #[test]
+    fn test_vocabulary_file() {
+        let text = [
+            "ignore_case = true",
+            "",
+            "[words]",
+            "un = 1",
+            "deux = 2",
+            "vingt-et-un = 21",
+        ]
+        .join("\n");
+        let vocabulary = text.parse::<Vocabulary>().unwrap();
+
+        assert_eq!(vocabulary.calibrate("Vingt-et-Un"), Some(21));
+        assert_eq!(vocabulary.calibrate("DEUXun"), Some(21));
+
+        let err = "[words]\nun = -1".parse::<Vocabulary>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "malformed vocabulary: words.un is not a number of zero or more"
+        );
+        let err = "lang = \"fr\"".parse::<Vocabulary>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "malformed vocabulary: unexpected string `lang`"
+        );
+    }
+
+    #[test]
+    fn test_explain_table() {
+        let input = ["two1nine", "eightwo", "xyz"].join("\n");
+
+        assert_eq!(
+            Vocabulary::english().explain(&input).to_string(),
+            [
+                "Line  First  Last  Value  Tokens",
+                "1     2      9     29     0:two 3:1 4:nine",
+                "2     8      2     82     0:eight 4:two",
+                "3     -      -     -",
+                "",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            Vocabulary::default().explain("two1nine").lines[0].tokens,
+            vec![Token {
+                offset: 3,
+                text: "1".to_string(),
+                value: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn test_explain_json() {
+        let input = ["a7", "", "q\"x"].join("\n");
+        let explanation = Vocabulary::english().with_word("\"x", 5).explain(&input);
+
+        assert_eq!(
+            explanation.to_json(),
+            [
+                r#"{"lines":["#,
+                r#"{"line":1,"tokens":[{"offset":1,"text":"7","value":7}],"first":7,"last":7,"value":77},"#,
+                r#"{"line":2,"tokens":[],"first":null,"last":null,"value":null},"#,
+                r#"{"line":3,"tokens":[{"offset":1,"text":"\"x","value":5}],"first":5,"last":5,"value":55}"#,
+                r#"]}"#,
+            ]
+            .concat()
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn prop_explain_matches_calibrate(l in "(one|two|eight|nine|ONE|Eight|[0-9]|[a-zA-Z]|x|i|v|İ){0,12}") {
+            let twice = Vocabulary::new([("one", 1), ("eight", 8), ("one", 7), ("eight", 3)]);
+            let vocabularies = [
+                Vocabulary::default(),
+                Vocabulary::english(),
+                Vocabulary::english().with_ignore_case(true),
+                Vocabulary::roman(),
+                Vocabulary::roman().with_ignore_case(true),
+                twice,
+            ];
+            for vocabulary in vocabularies {
+                let explanation = vocabulary.explain(&l);
+                let value = explanation.lines.first().and_then(|e| e.value);
+
+                prop_assert_eq!(value, vocabulary.calibrate(&l));
+                for t in explanation.lines.iter().flat_map(|e| e.tokens.iter()) {
+                    prop_assert!(l[t.offset..].starts_with(&t.text));
+                }
+            }
+            prop_assert_eq!(Vocabulary::default().calibrate(&l), digits(&l));
+        }
+    }
+
+    #[test]
+    fn test_explain_ignore_case() {
+        let line = "İEightTWO";
+        let explanation = Vocabulary::english().with_ignore_case(true).explain(line);
+
+        let tokens = &explanation.lines[0].tokens;
+        assert_eq!(
+            tokens
+                .iter()
+                .map(|t| (t.offset, t.text.as_str()))
+                .collect::<Vec<_>>(),
+            [(2, "Eight"), (7, "TWO")]
+        );
+        for t in tokens {
+            assert!(line[t.offset..].starts_with(&t.text));
+        }
+        assert_eq!(explanation.lines[0].value, Some(82));
+
+        let explanation = Vocabulary::roman().with_ignore_case(true).explain("İV");
+        let tokens = &explanation.lines[0].tokens;
+        assert_eq!((tokens[0].offset, tokens[0].text.as_str()), (0, "İ"));
+        assert_eq!((tokens[1].offset, tokens[1].text.as_str()), (2, "V"));
+        assert_eq!(
+            explanation.lines[0].value,
+            Vocabulary::roman().with_ignore_case(true).calibrate("İV")
+        );
+    }
+
+    #[test]
+    fn test_override_word() {
+        let vocabulary = Vocabulary::english().with_word("one", 7);
+        assert_eq!(vocabulary.calibrate("one"), Some(77));
+        assert_eq!(vocabulary.calibrate("twone"), Some(27));
+        assert_eq!(vocabulary.words.len(), Vocabulary::english().words.len());
+
+        let vocabulary = Vocabulary::english()
+            .with_ignore_case(true)
+            .with_word("ONE", 7);
+        assert_eq!(vocabulary.calibrate("One"), Some(77));
+
+        assert_eq!(
+            Vocabulary::new([("one", 1), ("one", 7)]).calibrate("one"),
+            Some(77)
+        );
+        assert_eq!(
+            Vocabulary::english().with_word("1", 7).calibrate("1"),
+            Some(77)
+        );
+    }
+
+    #[test]
+    fn test_explain_duplicate_words() {
+        let input = ["one", "eightwone", "2one", "one3"].join("\n");
+
+        for vocabulary in [
+            Vocabulary::new([("one", 1), ("one", 7)]),
+            Vocabulary::english().with_word("one", 7),
+        ] {
+            let explanation = vocabulary.explain(&input);
+
+            for (l, e) in input.lines().zip(explanation.lines.iter()) {
+                assert_eq!(e.value, vocabulary.calibrate(l), "line {:?}", l);
+            }
+        }
+    }
+
+    #[test]
+    fn test_digitless() {
+        let input = ["1abc2", "", "pqrstu", "treb7uchet", "", ""].join("\n");
+        let english = Vocabulary::english();
+
+        assert_eq!(
+            day1_part1(&input, Digitless::Error),
+            Err(NoDigit { line: 2 })
+        );
+        assert_eq!(day1_part1(&input, Digitless::Skip), Ok(12 + 77));
+        assert_eq!(day1_part1(&input, Digitless::Zero), Ok(12 + 77));
+        assert_eq!(
+            day1_part2("one\nabc", &english, Digitless::Error),
+            Err(NoDigit { line: 2 })
+        );
+        assert_eq!(day1_part2("one\nabc", &english, Digitless::Skip), Ok(11));
+
+        assert_eq!(
+            calibrations(&input, digits, Digitless::Skip),
+            Ok(vec![(1, 12), (4, 77)])
+        );
+        assert_eq!(
+            calibrations(&input, digits, Digitless::Zero),
+            Ok(vec![(1, 12), (2, 0), (3, 0), (4, 77)])
+        );
+
+        for digitless in [Digitless::Error, Digitless::Skip, Digitless::Zero] {
+            assert_eq!(
+                day1_part1_stream(input.as_bytes(), digitless).ok(),
+                day1_part1(&input, digitless).ok()
+            );
+            assert_eq!(
+                day1_part2_stream("one\nabc".as_bytes(), &english, digitless).ok(),
+                day1_part2("one\nabc", &english, digitless).ok()
+            );
+        }
+    }
+
+    #[test]
+    fn test_trailing_newlines() {
+        let input = "1abc2\ntreb7uchet\n\n\r\n";
+
+        assert_eq!(day1_part1(input, Digitless::Error), Ok(12 + 77));
+        assert_eq!(
+            day1_part2(input, &Vocabulary::english(), Digitless::Error),
+            Ok(12 + 77)
+        );
+        assert_eq!(
+            day1_part1_stream(input.as_bytes(), Digitless::Error).unwrap(),
+            12 + 77
+        );
+        assert_eq!(
+            day1_part2_stream(input.as_bytes(), &Vocabulary::english(), Digitless::Error).unwrap(),
+            12 + 77
+        );
+        assert_eq!(
+            Vocabulary::english()
+                .explain(input)
+                .lines
+                .iter()
+                .map(|e| (e.line, e.value))
+                .collect::<Vec<_>>(),
+            vec![(1, Some(12)), (2, Some(77))]
+        );
+        assert_eq!(day1_part1("", Digitless::Error), Ok(0));
+        assert_eq!(
+            day1_part1_stream("".as_bytes(), Digitless::Error).unwrap(),
+            0
+        );
     }
 }
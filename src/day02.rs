@@ -1,22 +1,82 @@
-use crate::day02::Cube::{Blue, Green, Red};
-use aoc_runner_derive::{aoc, aoc_generator};
+use crate::error::{checked_sum, Overflow, ParseError};
+use crate::par;
+use crate::stream::{for_each_line, StreamError};
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::io::BufRead;
 
+const VALUES: [(Cube, usize); 3] = [(Cube::RED, 12), (Cube::GREEN, 13), (Cube::BLUE, 14)];
+
+/// Cube is the colour of a cube, as numbered by the Palette that read its name. The colours of
+/// the puzzle have the same number in every palette.
 #[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
-pub enum Cube {
-    Red,
-    Green,
-    Blue,
-}
-
-impl From<String> for Cube {
-    fn from(value: String) -> Self {
-        match value.as_str() {
-            "red" => Red,
-            "green" => Green,
-            "blue" => Blue,
-            _ => unreachable!(),
+pub struct Cube(usize);
+
+impl Cube {
+    pub const RED: Cube = Cube(0);
+    pub const GREEN: Cube = Cube(1);
+    pub const BLUE: Cube = Cube(2);
+}
+
+/// Palette interns the colour names of an input, so any word names a colour while comparing
+/// and hashing colours stays as cheap as for an enum.
+#[derive(Debug, Clone)]
+pub struct Palette {
+    names: Vec<Box<str>>,
+    cubes: HashMap<Box<str>, Cube>,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        let mut palette = Palette {
+            names: vec![],
+            cubes: HashMap::new(),
+        };
+        for name in ["red", "green", "blue"] {
+            palette.intern(name);
+        }
+
+        palette
+    }
+}
+
+impl Palette {
+    /// intern returns the colour named `name`, numbering it if it is new.
+    pub fn intern(&mut self, name: &str) -> Cube {
+        if let Some(&cube) = self.cubes.get(name) {
+            return cube;
         }
+
+        let cube = Cube(self.names.len());
+        self.names.push(name.into());
+        self.cubes.insert(name.into(), cube);
+        cube
+    }
+
+    /// get returns the colour named `name`, if the palette has it.
+    pub fn get(&self, name: &str) -> Option<Cube> {
+        self.cubes.get(name).copied()
+    }
+
+    pub fn name(&self, cube: Cube) -> &str {
+        &self.names[cube.0]
+    }
+}
+
+/// ParsedInput is the games along with the palette naming their colours.
+#[derive(Debug)]
+pub struct ParsedInput {
+    games: Vec<Game>,
+    palette: Palette,
+}
+
+impl ParsedInput {
+    pub fn games(&self) -> &[Game] {
+        &self.games
+    }
+
+    pub fn palette(&self) -> &Palette {
+        &self.palette
     }
 }
 
@@ -26,11 +86,12 @@ pub struct Set {
 }
 
 impl Set {
-    fn count(&self, cube: &Cube) -> usize {
-        self.groups
-            .iter()
-            .filter_map(|(c, n)| if c == cube { Some(n) } else { None })
-            .sum()
+    fn count(&self, cube: &Cube) -> Result<usize, Overflow> {
+        checked_sum(
+            self.groups
+                .iter()
+                .filter_map(|(c, n)| if c == cube { Some(*n) } else { None }),
+        )
     }
 }
 
@@ -41,47 +102,58 @@ pub struct Game {
 }
 
 impl Game {
-    fn is_possible(&self, values: &[(Cube, usize)]) -> bool {
-        for (c, v) in values {
-            for s in self.sets.iter() {
-                if s.count(c) > *v {
-                    return false;
-                }
-            }
-        }
-
-        true
+    /// is_possible tells whether every set could come out of a bag holding `limits` cubes. A
+    /// colour without a limit is not in the bag at all, and no bag holds more than usize::MAX.
+    pub fn is_possible(&self, limits: &HashMap<Cube, usize>) -> bool {
+        self.sets.iter().all(|s| {
+            s.groups.iter().all(|(c, _)| {
+                s.count(c)
+                    .is_ok_and(|n| n <= limits.get(c).copied().unwrap_or(0))
+            })
+        })
     }
 
-    fn least_possible(&self) -> Set {
-        let mut map = HashMap::new();
+    /// least_possible returns the fewest cubes of every colour seen that make the game possible,
+    /// in the order the colours first appear.
+    pub fn least_possible(&self) -> Result<Set, Overflow> {
+        let mut groups: Vec<(Cube, usize)> = vec![];
         for s in self.sets.iter() {
-            for (g, s) in s.groups.iter() {
-                let e = map.entry(*g).or_insert(0);
+            for (c, _) in s.groups.iter() {
+                let n = s.count(c)?;
 
-                if *e < *s {
-                    *e = *s
+                match groups.iter_mut().find(|(g, _)| g == c) {
+                    Some((_, least)) => *least = n.max(*least),
+                    None => groups.push((*c, n)),
                 }
             }
         }
 
-        Set {
-            groups: map.into_iter().collect(),
-        }
+        Ok(Set { groups })
+    }
+
+    /// power multiplies together the fewest cubes of each colour that make the game possible.
+    fn power(&self) -> Result<usize, Overflow> {
+        self.least_possible()?
+            .groups
+            .iter()
+            .try_fold(1_usize, |acc, (_, n)| acc.checked_mul(*n).ok_or(Overflow))
     }
 }
 
 peg::parser! {
-  grammar game_parser() for str {
+  grammar game_parser(palette: &RefCell<Palette>) for str {
     pub rule number() -> usize
       = n:$(['0'..='9']+) {? n.parse().or(Err("u32")) }
 
-    pub rule identifier() -> String
-      = id:$(['a'..='z' | 'A'..='Z' | '0'..='9']+) { id.to_string() }
+    pub rule identifier() -> &'input str
+      = $(['a'..='z' | 'A'..='Z' | '0'..='9']+)
+
+    pub rule cube() -> Cube
+      = name:(identifier()) { palette.borrow_mut().intern(name) }
 
     pub rule group() -> (Cube, usize)
-      = n:(number()) " " name:(identifier())  {
-      (Cube::from(name), n)
+      = n:(number()) " " cube:(cube())  {
+      (cube, n)
     }
 
     pub rule set() -> Set
@@ -89,41 +161,66 @@ peg::parser! {
 
     pub rule game() -> Game
       = "Game " id:(number()) ": "  sets:(set() ** "; ")  { Game{id, sets} }
+
+    pub rule games() -> Vec<Game> = games:(game() ** ("\r"? "\n")) "\r"? { games }
   }
 }
 
-#[aoc_generator(day2)]
-fn parse_day2(input: &str) -> Vec<Game> {
-    input
-        .lines()
-        .map(game_parser::game)
-        .filter_map(Result::ok)
-        .collect()
+pub fn parse_day2(input: &str) -> Result<ParsedInput, ParseError> {
+    let palette = RefCell::default();
+    let games =
+        game_parser::games(input, &palette).map_err(|e| ParseError::from_peg(2, input, e))?;
+
+    Ok(ParsedInput {
+        games,
+        palette: palette.into_inner(),
+    })
+}
+
+pub fn day2_part1(input: &ParsedInput) -> Result<usize, Overflow> {
+    possible_ids(&input.games, &HashMap::from(VALUES))
 }
 
-#[aoc(day2, part1)]
-fn day2_part1(games: &[Game]) -> usize {
-    const VALUES: [(Cube, usize); 3] = [(Red, 12), (Green, 13), (Blue, 14)];
+/// possible_ids sums the ids of the games that are possible with `limits` cubes in the bag.
+pub fn possible_ids(games: &[Game], limits: &HashMap<Cube, usize>) -> Result<usize, Overflow> {
+    checked_sum(games.iter().filter(|g| g.is_possible(limits)).map(|g| g.id))
+}
 
-    games
-        .iter()
-        .filter_map(|g| {
-            if g.is_possible(&VALUES) {
-                Some(g.id)
-            } else {
-                None
-            }
-        })
-        .sum()
+pub fn day2_part2(input: &ParsedInput) -> Result<usize, Overflow> {
+    checked_sum(
+        par::map(&input.games, Game::power)
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?,
+    )
+}
+
+/// day2_part1_stream is day2_part1 reading one game at a time.
+pub fn day2_part1_stream(input: impl BufRead) -> Result<usize, StreamError> {
+    let limits = HashMap::from(VALUES);
+
+    sum_stream(input, |g| Ok(if g.is_possible(&limits) { g.id } else { 0 }))
 }
 
-#[aoc(day2, part2)]
-fn day2_part2(games: &[Game]) -> usize {
-    games
-        .iter()
-        .map(Game::least_possible)
-        .map(|s| s.groups.iter().map(|(_, n)| n).product::<usize>())
-        .sum::<usize>()
+/// day2_part2_stream is day2_part2 reading one game at a time.
+pub fn day2_part2_stream(input: impl BufRead) -> Result<usize, StreamError> {
+    sum_stream(input, Game::power)
+}
+
+fn sum_stream(
+    input: impl BufRead,
+    value: impl Fn(&Game) -> Result<usize, Overflow>,
+) -> Result<usize, StreamError> {
+    // Only the colours named so far are kept, not the games.
+    let palette = RefCell::default();
+    let mut sum: usize = 0;
+    for_each_line(input, |line, l| {
+        let game = game_parser::game(l, &palette)
+            .map_err(|e| ParseError::from_peg(2, l, e).on_line(line))?;
+        sum = sum.checked_add(value(&game)?).ok_or(Overflow)?;
+        Ok(())
+    })?;
+
+    Ok(sum)
 }
 
 #[cfg(test)]
@@ -134,21 +231,27 @@ mod tests {
     fn test_parse_number() {
         let input = "123";
 
-        assert_eq!(game_parser::number(input), Ok(123))
+        assert_eq!(game_parser::number(input, &RefCell::default()), Ok(123))
     }
 
     #[test]
     fn test_parse_identifier() {
         let input = "green";
 
-        assert_eq!(game_parser::identifier(input), Ok("green".to_string()))
+        assert_eq!(
+            game_parser::identifier(input, &RefCell::default()),
+            Ok("green")
+        )
     }
 
     #[test]
     fn test_parse_group() {
         let input = "14 green";
 
-        assert_eq!(game_parser::group(input), Ok((Green, 14)))
+        assert_eq!(
+            game_parser::group(input, &RefCell::default()),
+            Ok((Cube::GREEN, 14))
+        )
     }
 
     #[test]
@@ -156,9 +259,9 @@ mod tests {
         let input = "3 blue, 4 red";
 
         assert_eq!(
-            game_parser::set(input),
+            game_parser::set(input, &RefCell::default()),
             Ok(Set {
-                groups: vec![(Blue, 3), (Red, 4)]
+                groups: vec![(Cube::BLUE, 3), (Cube::RED, 4)]
             })
         )
     }
@@ -168,24 +271,34 @@ mod tests {
         let input = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green";
 
         assert_eq!(
-            game_parser::game(input),
+            game_parser::game(input, &RefCell::default()),
             Ok(Game {
                 id: 1,
                 sets: vec![
                     Set {
-                        groups: vec![(Blue, 3), (Red, 4)]
+                        groups: vec![(Cube::BLUE, 3), (Cube::RED, 4)]
                     },
                     Set {
-                        groups: vec![(Red, 1), (Green, 2), (Blue, 6)]
+                        groups: vec![(Cube::RED, 1), (Cube::GREEN, 2), (Cube::BLUE, 6)]
                     },
                     Set {
-                        groups: vec![(Green, 2)]
+                        groups: vec![(Cube::GREEN, 2)]
                     },
                 ],
             })
         )
     }
 
+    #[test]
+    fn test_parse_crlf() {
+        let input = ["Game 1: 3 blue, 4 red", "Game 2: 1 red, 2 green"].join("\r\n");
+
+        let parsed = parse_day2(&input).unwrap();
+        assert_eq!(parsed.games.len(), 2);
+        assert_eq!(day2_part2(&parsed), Ok(14));
+        assert_eq!(day2_part2_stream(input.as_bytes()).unwrap(), 14);
+    }
+
     #[test]
     fn test_part1() {
         let input = [
@@ -196,7 +309,8 @@ mod tests {
             "Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green",
         ]
         .join("\n");
-        assert_eq!(day2_part1(&parse_day2(&input)), 8);
+        assert_eq!(day2_part1(&parse_day2(&input).unwrap()), Ok(8));
+        assert_eq!(day2_part1_stream(input.as_bytes()).unwrap(), 8);
     }
 
     #[test]
@@ -209,6 +323,92 @@ mod tests {
             "Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green",
         ]
         .join("\n");
-        assert_eq!(day2_part2(&parse_day2(&input)), 2286);
+        assert_eq!(day2_part2(&parse_day2(&input).unwrap()), Ok(2286));
+        assert_eq!(day2_part2_stream(input.as_bytes()).unwrap(), 2286);
+    }
+
+    #[test]
+    fn test_parse_missing_colour() {
+        let input = ["Game 1: 3 blue, 4 red", "Game 2: 1 blue, 2; 3 green"].join("\n");
+
+        let err = parse_day2(&input).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 18));
+        assert_eq!(err.snippet, "Game 2: 1 blue, 2; 3 green");
+        assert!(err.expected.contains("\" \""));
+
+        let Err(StreamError::Parse(streamed)) = day2_part1_stream(input.as_bytes()) else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(streamed, err);
+    }
+
+    #[test]
+    fn test_palette() {
+        let mut palette = Palette::default();
+        assert_eq!(palette.intern("red"), Cube::RED);
+        assert_eq!(palette.intern("blue"), Cube::BLUE);
+
+        let yellow = palette.intern("yellow");
+        assert_eq!(palette.intern("yellow"), yellow);
+        assert_ne!(palette.intern("Yellow"), yellow);
+        assert_eq!(palette.get("yellow"), Some(yellow));
+        assert_eq!(palette.get("magenta"), None);
+        assert_eq!(palette.name(yellow), "yellow");
+        assert_eq!(palette.name(Cube::GREEN), "green");
+    }
+
+    #[test]
+    fn test_open_colours() {
+        let input = [
+            "Game 1: 3 yellow, 4 red; 1 magenta, 2 yellow",
+            "Game 2: 1 blue, 2 green",
+            "Game 3: 5 yellow, 1 yellow",
+        ]
+        .join("\n");
+        let parsed = parse_day2(&input).unwrap();
+        let palette = parsed.palette();
+        let (yellow, magenta) = (
+            palette.get("yellow").unwrap(),
+            palette.get("magenta").unwrap(),
+        );
+
+        let limits = HashMap::from([(yellow, 5), (Cube::RED, 4), (magenta, 1)]);
+        let games = parsed.games();
+        assert_eq!(possible_ids(games, &limits), Ok(1));
+        assert_eq!(day2_part1(&parsed), Ok(2));
+
+        assert_eq!(
+            games[0].least_possible(),
+            Ok(Set {
+                groups: vec![(yellow, 3), (Cube::RED, 4), (magenta, 1)]
+            })
+        );
+        assert_eq!(
+            games[2].least_possible(),
+            Ok(Set {
+                groups: vec![(yellow, 6)]
+            })
+        );
+        assert_eq!(day2_part2(&parsed), Ok(3 * 4 + 2 + 6));
+    }
+
+    #[test]
+    fn test_overflow() {
+        let max = usize::MAX;
+        let input = format!("Game {}: 1 red\nGame 1: 1 red", max);
+        let games = parse_day2(&input).unwrap();
+        assert_eq!(day2_part1(&games), Err(Overflow));
+        assert!(matches!(
+            day2_part1_stream(input.as_bytes()),
+            Err(StreamError::Solve(_))
+        ));
+
+        let input = format!("Game 1: {} red, 1 red", max);
+        let games = parse_day2(&input).unwrap();
+        assert_eq!(day2_part1(&games), Ok(0));
+        assert_eq!(day2_part2(&games), Err(Overflow));
+
+        let input = format!("Game 1: {} red, 2 blue", max);
+        assert_eq!(day2_part2(&parse_day2(&input).unwrap()), Err(Overflow));
     }
 }
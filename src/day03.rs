@@ -1,139 +1,165 @@
-use aoc_runner_derive::{aoc, aoc_generator};
-use std::collections::HashSet;
+use crate::error::{checked_sum, Overflow, ParseError};
+use crate::grid::{Grid, GridError, Point, Span};
 
-#[derive(Debug, Eq, PartialEq, Hash)]
-struct Point {
-    x: usize,
-    y: usize,
+#[derive(Debug)]
+pub struct ParsedInput {
+    board: Grid<char>,
+    numbers: Vec<(Span, usize)>,
+    marks: Vec<(Point, char)>,
 }
 
-#[derive(Debug, Eq, PartialEq, Hash)]
-struct Span {
-    from: Point,
-    to: Point,
+/// Style picks how render draws the schematic.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Style {
+    /// Colour part numbers green, isolated numbers red and gears yellow.
+    Ansi,
+    /// The same text without any escape codes.
+    Plain,
 }
 
-impl Span {
-    fn has_adjacent_point(&self, p: &Point) -> bool {
-        let (xf, xt, y) = (
-            self.from.x as isize,
-            self.to.x as isize,
-            self.from.y as isize,
-        );
-
-        if !(y - 1..=y + 1).contains(&(p.y as isize)) {
-            return false;
-        }
-
-        if !(xf - 1..=xt + 1).contains(&(p.x as isize)) {
-            return false;
-        }
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const YELLOW: &str = "\x1b[33m";
+const RESET: &str = "\x1b[0m";
 
-        true
+impl ParsedInput {
+    /// is_part tells whether the number at `span` touches a mark, which makes it count in part 1.
+    fn is_part(&self, span: &Span) -> bool {
+        self.marks.iter().any(|(p, _)| span.has_adjacent_point(p))
     }
-}
 
-struct ParsedInput {
-    numbers: Vec<(Span, usize)>,
-    marks: Vec<(Point, char)>,
-}
+    /// gears returns every `*` next to exactly two numbers, along with those numbers.
+    fn gears(&self) -> impl Iterator<Item = (Point, usize, usize)> + '_ {
+        self.marks
+            .iter()
+            .filter(|(_, c)| c == &'*')
+            .filter_map(|(p, _)| {
+                let mut numbers = self
+                    .numbers
+                    .iter()
+                    .filter(|(s, _)| s.has_adjacent_point(p))
+                    .map(|(_, n)| *n);
 
-#[aoc_generator(day3)]
-fn parse_day3(input: &str) -> ParsedInput {
-    let board = input
-        .lines()
-        .map(|l| l.chars().collect::<Vec<_>>())
-        .collect::<Vec<_>>();
-
-    let mut numbers = vec![];
-    let mut marks = vec![];
-
-    for y in 0..board.len() {
-        let mut x = 0;
-        while x < board[0].len() {
-            match board[y][x] {
-                '0'..='9' => {
-                    let (span, number) = eat_number(&board, y, x);
-
-                    x = span.to.x + 1;
-                    numbers.push((span, number));
-                }
-                '.' => {
-                    x += 1;
-                }
-                mark => {
-                    marks.push((Point { x, y }, mark));
-                    x += 1;
+                match (numbers.next(), numbers.next(), numbers.next()) {
+                    (Some(a), Some(b), None) => Some((*p, a, b)),
+                    _ => None,
                 }
-            }
-        }
+            })
     }
 
-    ParsedInput { numbers, marks }
-}
+    /// render draws the schematic row by row, following every row holding gears with their
+    /// ratios, e.g. `  467 x 35 = 16345`.
+    pub fn render(&self, style: Style) -> String {
+        let mut colours = self.board.map(|_| None);
+        for (span, _) in self.numbers.iter() {
+            let colour = if self.is_part(span) { GREEN } else { RED };
+            for p in span.points() {
+                colours[p] = Some(colour);
+            }
+        }
 
-#[aoc(day3, part1)]
-fn day3_part1(input: &ParsedInput) -> usize {
-    let ParsedInput { numbers, marks } = input;
+        let mut ratios = vec![vec![]; self.board.height()];
+        for (p, a, b) in self.gears() {
+            colours[p] = Some(YELLOW);
+            ratios[p.y].push(format!("{} x {} = {}", a, b, a as u128 * b as u128));
+        }
 
-    let mut hash = HashSet::new();
-    for (span, num) in numbers {
-        for (point, _) in marks.iter() {
-            if span.has_adjacent_point(point) {
-                hash.insert((span, num));
+        let mut out = String::new();
+        for (y, row) in self.board.rows().enumerate() {
+            let mut current = None;
+            for (x, c) in row.iter().enumerate() {
+                let colour = colours[Point::new(x, y)];
+                if style == Style::Ansi && colour != current {
+                    out.push_str(colour.unwrap_or(RESET));
+                    current = colour;
+                }
+                out.push(*c);
+            }
+            if current.is_some() {
+                out.push_str(RESET);
+            }
 
-                break;
+            if !ratios[y].is_empty() {
+                out.push_str("  ");
+                out.push_str(&ratios[y].join(", "));
             }
+            out.push('\n');
         }
-    }
 
-    hash.iter().map(|(_, &n)| n).sum()
+        out
+    }
 }
 
-#[aoc(day3, part2)]
-fn day3_part2(input: &ParsedInput) -> usize {
-    let ParsedInput { numbers, marks } = input;
+pub fn parse_day3(input: &str) -> Result<ParsedInput, ParseError> {
+    let board = match Grid::parse(input) {
+        Ok(board) => board,
+        Err(GridError::Ragged {
+            row,
+            expected,
+            found,
+        }) => {
+            let p = Point::new(found.min(expected), row);
+            return Err(error_at(input, p, format!("{} columns", expected)));
+        }
+    };
 
-    marks
-        .iter()
-        .filter(|(_, c)| c == &'*')
-        .map(|(p, _)| {
-            numbers
-                .iter()
-                .filter(|(s, _)| s.has_adjacent_point(p))
-                .map(|(_, n)| n)
-                .collect::<Vec<_>>()
+    let numbers = board
+        .runs(char::is_ascii_digit)
+        .map(|span| {
+            let digits = board.span(&span).iter().collect::<String>();
+
+            match digits.parse() {
+                Ok(n) => Ok((span, n)),
+                Err(_) => Err(error_at(input, span.from, "number")),
+            }
         })
-        .filter(|l| l.len() == 2)
-        .map(|l| l.iter().cloned().product::<usize>())
-        .sum()
+        .collect::<Result<_, _>>()?;
+
+    let marks = board
+        .iter()
+        .filter(|(_, c)| !c.is_ascii_digit() && **c != '.')
+        .map(|(p, &c)| (p, c))
+        .collect();
+
+    Ok(ParsedInput {
+        board,
+        numbers,
+        marks,
+    })
 }
 
-fn eat_number(board: &[Vec<char>], y: usize, x: usize) -> (Span, usize) {
-    let mut builder = "".to_string();
+/// error_at points at the character the grid holds at `p`, counting the bytes of the wider
+/// characters and line endings before it.
+fn error_at(input: &str, p: Point, expected: impl Into<String>) -> ParseError {
+    let start = input
+        .split_inclusive('\n')
+        .take(p.y)
+        .map(str::len)
+        .sum::<usize>();
+    let line = input[start..].lines().next().unwrap_or("");
+    let column = line.char_indices().nth(p.x).map_or(line.len(), |(i, _)| i);
 
-    let mut cursor = x;
-    while cursor < board[0].len() {
-        match board[y][cursor] {
-            c @ '0'..='9' => {
-                builder.push(c);
-                cursor += 1;
-            }
-            _ => {
-                break;
-            }
-        }
-    }
+    ParseError::new(3, input, start + column, [expected])
+}
 
-    (
-        Span {
-            from: Point { x, y },
-            to: Point { x: cursor - 1, y },
-        },
-        builder.parse().unwrap(),
+pub fn day3_part1(input: &ParsedInput) -> Result<usize, Overflow> {
+    checked_sum(
+        input
+            .numbers
+            .iter()
+            .filter(|(span, _)| input.is_part(span))
+            .map(|(_, n)| *n),
     )
 }
 
+pub fn day3_part2(input: &ParsedInput) -> Result<usize, Overflow> {
+    input.gears().try_fold(0_usize, |acc, (_, a, b)| {
+        a.checked_mul(b)
+            .and_then(|r| acc.checked_add(r))
+            .ok_or(Overflow)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -153,7 +179,7 @@ mod tests {
             ".664.598..",
         ]
         .join("\n");
-        assert_eq!(day3_part1(&parse_day3(&input)), 4361);
+        assert_eq!(day3_part1(&parse_day3(&input).unwrap()), Ok(4361));
     }
 
     #[test]
@@ -171,6 +197,98 @@ mod tests {
             ".664.598..",
         ]
         .join("\n");
-        assert_eq!(day3_part2(&parse_day3(&input)), 467835);
+        assert_eq!(day3_part2(&parse_day3(&input).unwrap()), Ok(467835));
+    }
+
+    #[test]
+    fn test_render_plain() {
+        let input = [
+            "467..114..",
+            "...*......",
+            "..35..633.",
+            "......#...",
+            "617*......",
+            ".....+.58.",
+            "..592.....",
+            "......755.",
+            "...$.*....",
+            ".664.598..",
+        ]
+        .join("\n");
+
+        assert_eq!(
+            parse_day3(&input).unwrap().render(Style::Plain),
+            [
+                "467..114..",
+                "...*......  467 x 35 = 16345",
+                "..35..633.",
+                "......#...",
+                "617*......",
+                ".....+.58.",
+                "..592.....",
+                "......755.",
+                "...$.*....  755 x 598 = 451490",
+                ".664.598..",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_render_ansi() {
+        let input = ["12.7", "*3.."].join("\n");
+
+        assert_eq!(
+            parse_day3(&input).unwrap().render(Style::Ansi),
+            [
+                "\x1b[32m12\x1b[0m.\x1b[31m7\x1b[0m",
+                "\x1b[33m*\x1b[32m3\x1b[0m..  12 x 3 = 36",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_parse_ragged() {
+        let input = ["467..114..", "...*..", "..35..633."].join("\n");
+
+        let err = parse_day3(&input).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 7));
+        assert!(err.expected.contains("10 columns"));
+    }
+
+    #[test]
+    fn test_parse_error_after_wide_characters() {
+        let input = ["éé.", "ab"].join("\r\n");
+        let err = parse_day3(&input).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 3));
+        assert_eq!(err.snippet, "ab");
+
+        let input = [
+            format!("é{}", ".".repeat(31)),
+            format!("é.{}", "9".repeat(30)),
+        ]
+        .join("\r\n");
+        let err = parse_day3(&input).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 3));
+        assert!(err.expected.contains("number"));
+    }
+
+    #[test]
+    fn test_overflow() {
+        let big = usize::MAX;
+        let input = format!("{}*1", big);
+        assert_eq!(day3_part1(&parse_day3(&input).unwrap()), Err(Overflow));
+
+        let input = format!("{}*2", big);
+        let parsed = parse_day3(&input).unwrap();
+        assert_eq!(day3_part2(&parsed), Err(Overflow));
+        assert!(parsed.render(Style::Plain).ends_with(&format!(
+            "{} x 2 = {}\n",
+            big,
+            big as u128 * 2
+        )));
     }
 }
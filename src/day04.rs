@@ -1,5 +1,8 @@
-use aoc_runner_derive::{aoc, aoc_generator};
+use crate::error::{checked_sum, Overflow, ParseError};
+use crate::stream::{for_each_line, StreamError};
 use itertools::{min, Itertools};
+use std::collections::VecDeque;
+use std::io::BufRead;
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct Card {
@@ -18,6 +21,17 @@ impl Card {
             .get(&true)
             .unwrap_or(&0)
     }
+
+    /// points doubles for every winning number after the first.
+    fn points(&self) -> Result<usize, Overflow> {
+        match self.count() {
+            0 => Ok(0),
+            s => u32::try_from(s - 1)
+                .ok()
+                .and_then(|e| 2_usize.checked_pow(e))
+                .ok_or(Overflow),
+        }
+    }
 }
 
 peg::parser! {
@@ -36,22 +50,17 @@ peg::parser! {
   }
 }
 
-#[aoc_generator(day4)]
-fn parse_day4(input: &str) -> Vec<Card> {
-    card_parser::cards(input).unwrap()
+pub fn parse_day4(input: &str) -> Result<Vec<Card>, ParseError> {
+    card_parser::cards(input).map_err(|e| ParseError::from_peg(4, input, e))
 }
 
-#[aoc(day4, part1)]
-fn day4_part1(cards: &[Card]) -> usize {
-    cards
-        .iter()
-        .map(Card::count)
-        .map(|s| if s > 0 { 2_usize.pow((s - 1) as _) } else { 0 })
-        .sum()
+pub fn day4_part1(cards: &[Card]) -> Result<usize, Overflow> {
+    cards.iter().try_fold(0_usize, |acc, c| {
+        acc.checked_add(c.points()?).ok_or(Overflow)
+    })
 }
 
-#[aoc(day4, part2)]
-fn day4_part2(cards: &[Card]) -> usize {
+pub fn day4_part2(cards: &[Card]) -> Result<usize, Overflow> {
     struct G {
         count: usize,
         score: usize,
@@ -66,13 +75,60 @@ fn day4_part2(cards: &[Card]) -> usize {
         .collect();
 
     for i in 0..scores.len() {
-        let upper_bound = *min(&[i + scores[i].score, scores.len()]).unwrap();
+        let upper_bound = *min(&[i + scores[i].score, scores.len() - 1]).unwrap();
         for j in i + 1..=upper_bound {
-            scores[j].count += scores[i].count;
+            scores[j].count = scores[j]
+                .count
+                .checked_add(scores[i].count)
+                .ok_or(Overflow)?;
         }
     }
 
-    scores.iter().map(|r| r.count).sum()
+    checked_sum(scores.iter().map(|r| r.count))
+}
+
+/// day4_part1_stream is day4_part1 reading one card at a time.
+pub fn day4_part1_stream(input: impl BufRead) -> Result<usize, StreamError> {
+    let mut sum: usize = 0;
+    for_each_line(input, |line, l| {
+        sum = sum
+            .checked_add(parse_card(line, l)?.points()?)
+            .ok_or(Overflow)?;
+        Ok(())
+    })?;
+
+    Ok(sum)
+}
+
+/// day4_part2_stream is day4_part2 reading one card at a time. Only the copies won for the
+/// next few cards are kept, at most as many as a card has numbers.
+pub fn day4_part2_stream(input: impl BufRead) -> Result<usize, StreamError> {
+    let mut pending = VecDeque::new();
+    let mut sum: usize = 0;
+    for_each_line(input, |line, l| {
+        let copies = pending
+            .pop_front()
+            .unwrap_or(0_usize)
+            .checked_add(1)
+            .ok_or(Overflow)?;
+        let count = parse_card(line, l)?.count();
+
+        if pending.len() < count {
+            pending.resize(count, 0);
+        }
+        for p in pending.iter_mut().take(count) {
+            *p = p.checked_add(copies).ok_or(Overflow)?;
+        }
+
+        sum = sum.checked_add(copies).ok_or(Overflow)?;
+        Ok(())
+    })?;
+
+    Ok(sum)
+}
+
+fn parse_card(line: usize, l: &str) -> Result<Card, ParseError> {
+    card_parser::card(l).map_err(|e| ParseError::from_peg(4, l, e).on_line(line))
 }
 
 #[cfg(test)]
@@ -121,7 +177,8 @@ mod tests {
             "Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11",
         ]
         .join("\n");
-        assert_eq!(day4_part1(&parse_day4(&input)), 13);
+        assert_eq!(day4_part1(&parse_day4(&input).unwrap()), Ok(13));
+        assert_eq!(day4_part1_stream(input.as_bytes()).unwrap(), 13);
     }
 
     #[test]
@@ -135,6 +192,33 @@ mod tests {
             "Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11",
         ]
         .join("\n");
-        assert_eq!(day4_part2(&parse_day4(&input)), 30);
+        assert_eq!(day4_part2(&parse_day4(&input).unwrap()), Ok(30));
+        assert_eq!(day4_part2_stream(input.as_bytes()).unwrap(), 30);
+    }
+
+    #[test]
+    fn test_part2_wins_past_the_end() {
+        let input = ["Card 1: 1 2 3 | 1 2 3", "Card 2: 4 5 | 4 6"].join("\n");
+
+        assert_eq!(day4_part2(&parse_day4(&input).unwrap()), Ok(3));
+        assert_eq!(day4_part2_stream(input.as_bytes()).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let numbers = (1..=65).map(|n| n.to_string()).join(" ");
+        let input = format!("Card 1: {} | {}", numbers, numbers);
+        assert_eq!(day4_part1(&parse_day4(&input).unwrap()), Err(Overflow));
+        assert!(matches!(
+            day4_part1_stream(input.as_bytes()),
+            Err(StreamError::Solve(_))
+        ));
+
+        let input = vec!["Card 1: 1 | 1 1 1 1"; 100].join("\n");
+        assert_eq!(day4_part2(&parse_day4(&input).unwrap()), Err(Overflow));
+        assert!(matches!(
+            day4_part2_stream(input.as_bytes()),
+            Err(StreamError::Solve(_))
+        ));
     }
 }
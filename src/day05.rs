@@ -1,5 +1,9 @@
-use aoc_runner_derive::{aoc, aoc_generator};
-use itertools::{max, min, Itertools};
+use crate::error::{Overflow, ParseError};
+use crate::interval::{Interval, IntervalSet};
+use crate::par;
+use itertools::Itertools;
+use std::error::Error;
+use std::fmt;
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub struct Range {
@@ -7,86 +11,24 @@ pub struct Range {
     length: usize,
 }
 
-#[derive(Debug, Eq, PartialEq, Clone)]
-struct ClipResult {
-    contained: Vec<Range>,
-    un_contained: Vec<Range>,
-}
-
-#[derive(Debug, Eq, PartialEq, Clone)]
-struct SplitResult {
-    contained: Vec<(Range, CompoundRange)>,
-    un_contained: Vec<Range>,
-}
-
 impl Range {
-    fn end(&self) -> usize {
-        self.start + self.length
+    pub fn start(&self) -> usize {
+        self.start
     }
 
-    fn clip(&self, cheese: &Range) -> ClipResult {
-        let mut cheese = *cheese;
-        let mut result = ClipResult {
-            contained: vec![],
-            un_contained: vec![],
-        };
-
-        // This means our chees will be cut from the left.
-        if cheese.start < self.start {
-            let end = *min(&[cheese.end(), self.start]).unwrap();
-
-            result.un_contained.push(Range {
-                start: cheese.start,
-                length: end - cheese.start,
-            });
-
-            cheese.length -= end - cheese.start;
-            cheese.start = end;
-        }
-
-        // This means our chees will be cut from the right.
-        if cheese.end() > self.end() {
-            let start = *max(&[cheese.start, self.end()]).unwrap();
-
-            result.un_contained.push(Range {
-                start,
-                length: cheese.end() - start,
-            });
-
-            cheese.length -= cheese.end() - start;
-        }
-
-        // If contained or on the thing.
-        if cheese.length > 0 {
-            result.contained.push(cheese)
-        }
-
-        result
+    pub fn length(&self) -> usize {
+        self.length
     }
 
-    fn split_on_ranges(&self, ranges: &[CompoundRange]) -> SplitResult {
-        let mut result = SplitResult {
-            contained: vec![],
-            un_contained: vec![*self],
-        };
-
-        for cr in ranges {
-            let mut new_un_contained = vec![];
-
-            for uc in result.un_contained {
-                let sub = cr.source.clip(&uc);
-
-                for r in sub.contained {
-                    result.contained.push((r, *cr))
-                }
-
-                new_un_contained.extend(sub.un_contained);
-            }
-
-            result.un_contained = new_un_contained;
-        }
+    /// end is the first value past the range.
+    pub fn end(&self) -> usize {
+        self.start + self.length
+    }
+}
 
-        result
+impl From<Range> for Interval {
+    fn from(r: Range) -> Self {
+        Interval::new(r.start, r.end())
     }
 }
 
@@ -110,16 +52,29 @@ impl CompoundRange {
         }
     }
 
+    pub fn source(&self) -> Range {
+        self.source
+    }
+
+    pub fn destination(&self) -> Range {
+        self.destination
+    }
+
     fn map(&self, n: usize) -> Option<usize> {
         if self.source.start <= n && n < self.source.start + self.source.length {
-            Some(self.destination.start + n - self.source.start)
+            Some(self.destination.start + (n - self.source.start))
         } else {
             None
         }
     }
+
+    /// restrict narrows the range down to the part of its source covered by `i`.
+    fn restrict(&self, i: &Interval) -> CompoundRange {
+        CompoundRange::new(self.map(i.start).unwrap(), i.start, i.len())
+    }
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Map {
     from: String,
     to: String,
@@ -127,8 +82,124 @@ pub struct Map {
 }
 
 impl Map {
-    fn map(&self, n: usize) -> usize {
-        self.ranges.iter().find_map(|r| r.map(n)).unwrap_or(n)
+    /// new keeps the ranges sorted by source and non-overlapping, so map can binary search them.
+    /// Where sources overlap the earlier range wins, and ranges that map onto themselves are
+    /// dropped since every value outside the table is already mapped to itself.
+    fn new(from: String, to: String, ranges: Vec<CompoundRange>) -> Self {
+        let mut covered = IntervalSet::new();
+        let mut pieces = vec![];
+
+        for cr in ranges {
+            let source = IntervalSet::from(Interval::from(cr.source));
+
+            for i in source.difference(&covered).iter() {
+                pieces.push(cr.restrict(i));
+            }
+
+            covered = covered.union(&source);
+        }
+
+        pieces.sort_by_key(|cr| cr.source.start);
+
+        let mut ranges: Vec<CompoundRange> = vec![];
+        for cr in pieces {
+            if cr.source.start == cr.destination.start {
+                continue;
+            }
+
+            match ranges.last_mut() {
+                Some(last)
+                    if last.source.end() == cr.source.start
+                        && last.destination.end() == cr.destination.start =>
+                {
+                    last.source.length += cr.source.length;
+                    last.destination.length += cr.destination.length;
+                }
+                _ => ranges.push(cr),
+            }
+        }
+
+        Self { from, to, ranges }
+    }
+
+    /// ranges lists the ranges the map moves, sorted by source and never overlapping. Values
+    /// outside of them map to themselves.
+    pub fn ranges(&self) -> &[CompoundRange] {
+        &self.ranges
+    }
+
+    /// map looks `n` up with a binary search over the ranges.
+    pub fn map(&self, n: usize) -> usize {
+        let idx = self.ranges.partition_point(|r| r.source.end() <= n);
+
+        self.ranges.get(idx).and_then(|r| r.map(n)).unwrap_or(n)
+    }
+
+    /// map_interval splits `i` into the pieces this map moves as a block, paired with where
+    /// each piece starts after mapping.
+    fn map_interval(&self, i: Interval) -> Vec<(Interval, usize)> {
+        let mut pieces = vec![];
+        let mut cursor = i.start;
+
+        let idx = self.ranges.partition_point(|r| r.source.end() <= i.start);
+        for cr in self.ranges[idx..].iter() {
+            if cr.source.start >= i.end {
+                break;
+            }
+
+            if cr.source.start > cursor {
+                pieces.push((Interval::new(cursor, cr.source.start), cursor));
+            }
+
+            let piece = Interval::from(cr.source)
+                .intersection(&Interval::new(cursor, i.end))
+                .unwrap();
+            pieces.push((piece, cr.map(piece.start).unwrap()));
+            cursor = piece.end;
+        }
+
+        if cursor < i.end {
+            pieces.push((Interval::new(cursor, i.end), cursor));
+        }
+
+        pieces
+    }
+
+    /// compose returns the single map equivalent to applying self and then `other`.
+    pub fn compose(&self, other: &Map) -> Map {
+        let mut ranges = vec![];
+
+        for cr in self.ranges.iter() {
+            for (piece, start) in other.map_interval(cr.destination.into()) {
+                let source = cr.source.start + (piece.start - cr.destination.start);
+                ranges.push(CompoundRange::new(start, source, piece.len()));
+            }
+        }
+
+        // Values self leaves untouched are only moved by other.
+        let sources = self
+            .ranges
+            .iter()
+            .map(|cr| Interval::from(cr.source))
+            .collect::<IntervalSet>();
+        for cr in other.ranges.iter() {
+            for i in IntervalSet::from(Interval::from(cr.source))
+                .difference(&sources)
+                .iter()
+            {
+                ranges.push(cr.restrict(i));
+            }
+        }
+
+        Map::new(self.from.clone(), other.to.clone(), ranges)
+    }
+
+    /// map_set maps every value of `set` at once, the same way map does for a single value.
+    fn map_set(&self, set: &IntervalSet) -> IntervalSet {
+        set.iter()
+            .flat_map(|&i| self.map_interval(i))
+            .map(|(piece, start)| Interval::new(start, start + piece.len()))
+            .collect()
     }
 }
 
@@ -138,6 +209,19 @@ pub struct ParsedInput {
     maps: Vec<Map>,
 }
 
+impl ParsedInput {
+    /// chain composes every map into one, from seed straight to location.
+    pub fn chain(&self) -> Map {
+        let from = self.maps.first().map_or("seed", |m| &m.from).to_string();
+
+        self.maps
+            .iter()
+            .fold(Map::new(from.clone(), from, vec![]), |acc, m| {
+                acc.compose(m)
+            })
+    }
+}
+
 peg::parser! {
     grammar parser() for str {
         pub rule sep() -> String = n:$([' ']+) { n.to_string() }
@@ -151,12 +235,18 @@ peg::parser! {
         pub rule name() -> String = from:$(['a'..='z']+) { from.to_string() }
 
         pub rule range() -> CompoundRange
-            = destination:number() sep() source:number() sep() length:number() {CompoundRange::new(destination, source,length)}
+            = destination:number() sep() source:number() sep() length:number() {?
+                if destination.checked_add(length).is_some() && source.checked_add(length).is_some() {
+                    Ok(CompoundRange::new(destination, source, length))
+                } else {
+                    Err("a range that ends within usize")
+                }
+            }
 
         pub rule ranges() -> Vec<CompoundRange> = ranges:(range() ** "\n")
 
         pub rule map() -> Map
-            = from:name() "-to-" to:name() sep() "map:\n" ranges:ranges() { Map{from, to, ranges} }
+            = from:name() "-to-" to:name() sep() "map:\n" ranges:ranges() { Map::new(from, to, ranges) }
 
         pub rule maps() -> Vec<Map> = maps:(map() ** "\n\n")
 
@@ -165,76 +255,76 @@ peg::parser! {
     }
 }
 
-#[aoc_generator(day5)]
-fn parse_day5(input: &str) -> ParsedInput {
-    parser::input(input).unwrap()
+pub fn parse_day5(input: &str) -> Result<ParsedInput, ParseError> {
+    parser::input(input).map_err(|e| ParseError::from_peg(5, input, e))
 }
 
-#[aoc(day5, part1)]
-fn day5_part1(input: &ParsedInput) -> usize {
-    input
-        .seeds
-        .iter()
-        .map(|&s| input.maps.iter().fold(s, |acc, m| m.map(acc)))
-        .min()
-        .unwrap()
+/// day5_part1 returns None when there are no seeds to plant.
+pub fn day5_part1(input: &ParsedInput) -> Option<usize> {
+    let chain = input.chain();
+
+    par::map(&input.seeds, |&s| chain.map(s)).into_iter().min()
 }
 
-#[aoc(day5, part2)]
-fn day5_part2(input: &ParsedInput) -> usize {
-    let mut seeds = input
+pub fn day5_part2(input: &ParsedInput) -> Result<usize, SeedRangeError> {
+    if input.seeds.len() % 2 == 1 {
+        return Err(SeedRangeError::Unpaired(input.seeds[input.seeds.len() - 1]));
+    }
+
+    let seeds = input
         .seeds
-        .clone()
-        .into_iter()
-        .tuples::<(usize, usize)>()
-        .map(|(x, y)| {
-            (
-                Range {
-                    start: x,
-                    length: y,
-                },
-                0,
-            )
+        .iter()
+        .tuples()
+        .map(|(&start, &length)| {
+            let end = start.checked_add(length).ok_or(Overflow)?;
+            Ok(Interval::new(start, end))
         })
-        .collect::<Vec<_>>();
-
-    let mut minm = usize::MAX;
-    while let Some((range, stage)) = seeds.pop() {
-        let Range { start, length: _ } = range;
-        if stage >= input.maps.len() {
-            if start < minm {
-                minm = start;
-            }
+        .collect::<Result<IntervalSet, Overflow>>()?;
 
-            continue;
-        }
+    input
+        .maps
+        .iter()
+        .fold(seeds, |acc, m| m.map_set(&acc))
+        .min()
+        .ok_or(SeedRangeError::Empty)
+}
 
-        let SplitResult {
-            contained,
-            un_contained,
-        } = range.split_on_ranges(&input.maps[stage].ranges);
-
-        for (r, cr) in contained {
-            seeds.push((
-                Range {
-                    start: cr.map(r.start).unwrap(),
-                    ..r
-                },
-                stage + 1,
-            ));
-        }
+/// SeedRangeError is why day5_part2 has no answer.
+#[derive(Debug, Eq, PartialEq)]
+pub enum SeedRangeError {
+    /// There is not a single seed range to plant.
+    Empty,
+    /// A seed range ends past usize::MAX.
+    Overflow,
+    /// The seeds are odd in number, so the last one starts a range without a length.
+    Unpaired(usize),
+}
 
-        for r in un_contained {
-            seeds.push((r, stage + 1));
-        }
+impl From<Overflow> for SeedRangeError {
+    fn from(_: Overflow) -> Self {
+        SeedRangeError::Overflow
     }
+}
 
-    minm
+impl fmt::Display for SeedRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SeedRangeError::Empty => write!(f, "there is not a single seed range to plant"),
+            SeedRangeError::Overflow => write!(f, "a seed range ends past {}", usize::MAX),
+            SeedRangeError::Unpaired(start) => {
+                write!(f, "the seed range starting at {} has no length", start)
+            }
+        }
+    }
 }
 
+impl Error for SeedRangeError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
+    use std::collections::BTreeSet;
 
     #[test]
     fn test_parse_card() {
@@ -279,63 +369,63 @@ mod tests {
             Ok(ParsedInput {
                 seeds: vec![79, 14, 55, 13],
                 maps: vec![
-                    Map {
-                        from: "seed".to_string(),
-                        to: "soil".to_string(),
-                        ranges: vec![
+                    Map::new(
+                        "seed".to_string(),
+                        "soil".to_string(),
+                        vec![
                             CompoundRange::new(50, 98, 2),
                             CompoundRange::new(52, 50, 48),
-                        ],
-                    },
-                    Map {
-                        from: "soil".to_string(),
-                        to: "fertilizer".to_string(),
-                        ranges: vec![
+                        ]
+                    ),
+                    Map::new(
+                        "soil".to_string(),
+                        "fertilizer".to_string(),
+                        vec![
                             CompoundRange::new(0, 15, 37),
                             CompoundRange::new(37, 52, 2),
                             CompoundRange::new(39, 0, 15),
-                        ],
-                    },
-                    Map {
-                        from: "fertilizer".to_string(),
-                        to: "water".to_string(),
-                        ranges: vec![
+                        ]
+                    ),
+                    Map::new(
+                        "fertilizer".to_string(),
+                        "water".to_string(),
+                        vec![
                             CompoundRange::new(49, 53, 8),
                             CompoundRange::new(0, 11, 42),
                             CompoundRange::new(42, 0, 7),
                             CompoundRange::new(57, 7, 4),
-                        ],
-                    },
-                    Map {
-                        from: "water".to_string(),
-                        to: "light".to_string(),
-                        ranges: vec![
+                        ]
+                    ),
+                    Map::new(
+                        "water".to_string(),
+                        "light".to_string(),
+                        vec![
                             CompoundRange::new(88, 18, 7),
                             CompoundRange::new(18, 25, 70),
-                        ],
-                    },
-                    Map {
-                        from: "light".to_string(),
-                        to: "temperature".to_string(),
-                        ranges: vec![
+                        ]
+                    ),
+                    Map::new(
+                        "light".to_string(),
+                        "temperature".to_string(),
+                        vec![
                             CompoundRange::new(45, 77, 23),
                             CompoundRange::new(81, 45, 19),
                             CompoundRange::new(68, 64, 13),
-                        ],
-                    },
-                    Map {
-                        from: "temperature".to_string(),
-                        to: "humidity".to_string(),
-                        ranges: vec![CompoundRange::new(0, 69, 1), CompoundRange::new(1, 0, 69),],
-                    },
-                    Map {
-                        from: "humidity".to_string(),
-                        to: "location".to_string(),
-                        ranges: vec![
+                        ]
+                    ),
+                    Map::new(
+                        "temperature".to_string(),
+                        "humidity".to_string(),
+                        vec![CompoundRange::new(0, 69, 1), CompoundRange::new(1, 0, 69),]
+                    ),
+                    Map::new(
+                        "humidity".to_string(),
+                        "location".to_string(),
+                        vec![
                             CompoundRange::new(60, 56, 37),
                             CompoundRange::new(56, 93, 4),
-                        ],
-                    },
+                        ]
+                    ),
                 ],
             })
         )
@@ -379,7 +469,7 @@ mod tests {
             "56 93 4",
         ]
         .join("\n");
-        assert_eq!(day5_part1(&parse_day5(&input)), 35);
+        assert_eq!(day5_part1(&parse_day5(&input).unwrap()), Some(35));
     }
 
     #[test]
@@ -420,6 +510,249 @@ mod tests {
             "56 93 4",
         ]
         .join("\n");
-        assert_eq!(day5_part2(&parse_day5(&input)), 46);
+        assert_eq!(day5_part2(&parse_day5(&input).unwrap()), Ok(46));
+    }
+
+    #[test]
+    fn test_no_seed_range() {
+        let input = parse_day5("seeds: \n\na-to-b map:\n1 7 1").unwrap();
+
+        assert_eq!(day5_part1(&input), None);
+        assert_eq!(day5_part2(&input), Err(SeedRangeError::Empty));
+    }
+
+    #[test]
+    fn test_unpaired_seed() {
+        let input = parse_day5("seeds: 7\n\na-to-b map:\n1 7 1").unwrap();
+        assert_eq!(day5_part1(&input), Some(1));
+        assert_eq!(day5_part2(&input), Err(SeedRangeError::Unpaired(7)));
+
+        let input = parse_day5("seeds: 79 14 55\n\na-to-b map:\n1 7 1").unwrap();
+        assert_eq!(day5_part2(&input), Err(SeedRangeError::Unpaired(55)));
+    }
+
+    #[test]
+    fn test_overflow() {
+        let max = usize::MAX;
+        let input = parse_day5(&format!("seeds: {} 5\n\na-to-b map:\n1 7 1", max)).unwrap();
+        assert_eq!(day5_part1(&input), Some(5));
+        assert_eq!(day5_part2(&input), Err(SeedRangeError::Overflow));
+
+        let input = parse_day5(&format!(
+            "seeds: {} 1\n\na-to-b map:\n{} {} 1",
+            max - 1,
+            max - 1,
+            max - 1
+        ));
+        assert_eq!(day5_part2(&input.unwrap()), Ok(max - 1));
+
+        let err = parse_day5(&format!("seeds: 1\n\na-to-b map:\n{} 0 2", max)).unwrap_err();
+        assert_eq!((err.line, err.column), (4, 25));
+        assert!(err.expected.contains("a range that ends within usize"));
+    }
+
+    #[test]
+    fn test_map_resolves_overlaps() {
+        let map = Map::new(
+            "a".to_string(),
+            "b".to_string(),
+            vec![
+                CompoundRange::new(100, 10, 10),
+                CompoundRange::new(200, 5, 10),
+                CompoundRange::new(30, 30, 5),
+            ],
+        );
+
+        assert_eq!(
+            map.ranges,
+            vec![
+                CompoundRange::new(200, 5, 5),
+                CompoundRange::new(100, 10, 10)
+            ]
+        );
+        assert_eq!(
+            (0..40).map(|n| map.map(n)).collect::<Vec<_>>(),
+            (0..5)
+                .chain(200..205)
+                .chain(100..110)
+                .chain(20..40)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_compose() {
+        let input = vec![
+            "seeds: 79 14 55 13",
+            "",
+            "seed-to-soil map:",
+            "50 98 2",
+            "52 50 48",
+            "",
+            "soil-to-fertilizer map:",
+            "0 15 37",
+            "37 52 2",
+            "39 0 15",
+            "",
+            "fertilizer-to-water map:",
+            "49 53 8",
+            "0 11 42",
+            "42 0 7",
+            "57 7 4",
+            "",
+            "water-to-light map:",
+            "88 18 7",
+            "18 25 70",
+            "",
+            "light-to-temperature map:",
+            "45 77 23",
+            "81 45 19",
+            "68 64 13",
+            "",
+            "temperature-to-humidity map:",
+            "0 69 1",
+            "1 0 69",
+            "",
+            "humidity-to-location map:",
+            "60 56 37",
+            "56 93 4",
+        ]
+        .join("\n");
+        let input = parse_day5(&input).unwrap();
+        let chain = input.chain();
+
+        assert_eq!(
+            (chain.from.as_str(), chain.to.as_str()),
+            ("seed", "location")
+        );
+        assert!(chain
+            .ranges()
+            .iter()
+            .tuple_windows()
+            .all(|(a, b)| a.source().end() <= b.source().start()));
+        for n in 0..200 {
+            assert_eq!(
+                chain.map(n),
+                input.maps.iter().fold(n, |acc, m| m.map(acc)),
+                "seed {}",
+                n
+            );
+        }
+    }
+
+    /// UNIVERSE bounds every value in the generated almanacs, so brute force stays cheap.
+    const UNIVERSE: usize = 48;
+
+    type Ranges = Vec<(usize, usize, usize)>;
+
+    fn ranges(count: std::ops::Range<usize>) -> impl Strategy<Value = Ranges> {
+        prop::collection::vec((0..UNIVERSE, 0..UNIVERSE, 0..12_usize), count)
+    }
+
+    fn seeds() -> impl Strategy<Value = Vec<(usize, usize)>> {
+        prop::collection::vec((0..UNIVERSE, 1..12_usize), 1..4)
+    }
+
+    fn map(ranges: &Ranges) -> Map {
+        let ranges = ranges
+            .iter()
+            .map(|&(d, s, l)| CompoundRange::new(d, s, l))
+            .collect();
+
+        Map::new("a".to_string(), "b".to_string(), ranges)
+    }
+
+    /// first_match maps `n` the way the puzzle describes it: through the first line whose
+    /// source covers it, or to itself.
+    fn first_match(ranges: &Ranges, n: usize) -> usize {
+        ranges
+            .iter()
+            .find(|&&(_, s, l)| s <= n && n < s + l)
+            .map_or(n, |&(d, s, _)| d + n - s)
+    }
+
+    /// render writes the almanac out in the puzzle's format, so it also goes through the parser.
+    fn render(seeds: &[(usize, usize)], maps: &[Ranges]) -> String {
+        let seeds = seeds
+            .iter()
+            .map(|(s, l)| format!("{} {}", s, l))
+            .collect::<Vec<_>>();
+        let mut text = format!("seeds: {}", seeds.join(" "));
+
+        for (i, ranges) in maps.iter().enumerate() {
+            let name = |i: usize| char::from(b'a' + i as u8);
+            text.push_str(&format!("\n\n{}-to-{} map:", name(i), name(i + 1)));
+
+            for (d, s, l) in ranges {
+                text.push_str(&format!("\n{} {} {}", d, s, l));
+            }
+        }
+
+        text
+    }
+
+    proptest! {
+        #[test]
+        fn prop_map_matches_first_match(ranges in ranges(0..5)) {
+            let map = map(&ranges);
+
+            for n in 0..2 * UNIVERSE {
+                prop_assert_eq!(map.map(n), first_match(&ranges, n), "n = {}", n);
+            }
+        }
+
+        #[test]
+        fn prop_map_set_matches_map(ranges in ranges(0..5), seeds in seeds()) {
+            let map = map(&ranges);
+            let set = seeds
+                .iter()
+                .map(|&(s, l)| Interval::new(s, s + l))
+                .collect::<IntervalSet>();
+
+            let mapped = map
+                .map_set(&set)
+                .iter()
+                .flat_map(|i| i.start..i.end)
+                .collect::<BTreeSet<_>>();
+            let expected = set
+                .iter()
+                .flat_map(|i| i.start..i.end)
+                .map(|n| first_match(&ranges, n))
+                .collect::<BTreeSet<_>>();
+
+            prop_assert_eq!(mapped, expected);
+        }
+
+        #[test]
+        fn prop_compose_matches_sequential(a in ranges(0..5), b in ranges(0..5)) {
+            let composed = map(&a).compose(&map(&b));
+
+            for n in 0..2 * UNIVERSE {
+                prop_assert_eq!(
+                    composed.map(n),
+                    first_match(&b, first_match(&a, n)),
+                    "n = {}",
+                    n
+                );
+            }
+        }
+
+        /// Both parts must agree with expanding every seed one by one through every line. The
+        /// grammar needs at least one line in every map.
+        #[test]
+        fn prop_parts_match_brute_force(
+            seeds in seeds(),
+            maps in prop::collection::vec(ranges(1..5), 1..5),
+        ) {
+            let text = render(&seeds, &maps);
+            let input = parse_day5(&text).unwrap();
+            let locate = |n: usize| maps.iter().fold(n, |acc, r| first_match(r, acc));
+
+            let part1 = seeds.iter().flat_map(|&(s, l)| [s, l]).map(locate).min().unwrap();
+            prop_assert_eq!(day5_part1(&input), Some(part1), "almanac:\n{}", text);
+
+            let part2 = seeds.iter().flat_map(|&(s, l)| s..s + l).map(locate).min().unwrap();
+            prop_assert_eq!(day5_part2(&input), Ok(part2), "almanac:\n{}", text);
+        }
     }
 }
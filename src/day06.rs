@@ -1,4 +1,6 @@
-use aoc_runner_derive::{aoc, aoc_generator};
+use crate::error::{Overflow, ParseError};
+use std::num::ParseIntError;
+use std::ops::RangeInclusive;
 use std::time;
 use std::time::Duration;
 
@@ -18,55 +20,171 @@ impl From<(usize, usize)> for Race {
     }
 }
 
-peg::parser! {
-  grammar game_parser() for str {
-    pub rule number() -> usize
-      = n:$(['0'..='9']+) {? n.parse().or(Err("u32")) }
+impl Race {
+    /// winning returns the hold times in milliseconds that beat the record, solving
+    /// `dt * (T - dt) > D` exactly. It is None when no hold time does.
+    pub fn winning(&self) -> Option<RangeInclusive<u128>> {
+        let (t, d) = (self.time.as_millis(), self.distance as u128);
+        let beats = |dt: u128| dt * (t - dt) > d;
+
+        // The best we can do is floor(T²/4), so there is nothing to find unless T² > 4D.
+        let discriminant = (t * t).checked_sub(4 * d).filter(|&disc| disc > 0)?;
+
+        // isqrt is exact, so this lands within one step of the real lower root.
+        let mut lo = (t - discriminant.isqrt()) / 2;
+        while lo > 0 && beats(lo - 1) {
+            lo -= 1;
+        }
+        while lo <= t / 2 && !beats(lo) {
+            lo += 1;
+        }
+
+        // The parabola is symmetric around T/2.
+        (lo <= t / 2).then(|| lo..=t - lo)
+    }
+
+    fn ways(&self) -> usize {
+        self.winning()
+            .map_or(0, |w| (w.end() - w.start() + 1) as usize)
+    }
+}
+
+/// numbers reads a whole line of numbers, or a single one made of all its digits when ignoring
+/// kerning.
+fn numbers(parts: &[&str], ignore_kerning: bool) -> Result<Vec<usize>, ParseIntError> {
+    if ignore_kerning {
+        parts.concat().parse().map(|n| vec![n])
+    } else {
+        parts.iter().map(|p| p.parse()).collect()
+    }
+}
 
+peg::parser! {
+  grammar game_parser(ignore_kerning: bool) for str {
         pub rule sep() -> String = n:$([' ']+) { n.to_string() }
 
+    rule digits() -> Vec<&'input str> = $(['0'..='9']+) ++ sep()
+
+    rule distances(races: usize) -> Vec<&'input str>
+      = "Distance:" sep() d:digits() {?
+            if d.len() == races { Ok(d) } else { Err("as many distances as times") }
+        }
+
     pub rule game() -> Vec<Race>
-      = "Time:" sep() times:(number() ** sep()) "\n" "Distance:" sep() distances:(number() ** sep())  {
-            times.into_iter().zip(distances).map(Race::from).collect()
+      = "Time:" sep() times:digits() "\n" distances:distances(times.len()) {?
+            let times = numbers(&times, ignore_kerning).or(Err("usize"))?;
+            let distances = numbers(&distances, ignore_kerning).or(Err("usize"))?;
+            Ok(times.into_iter().zip(distances).map(Race::from).collect())
         }
   }
 }
 
-#[aoc_generator(day6)]
-fn parse_day6(input: &str) -> Vec<Race> {
-    game_parser::game(input).unwrap()
+pub fn parse_day6(input: &str) -> Result<Vec<Race>, ParseError> {
+    game_parser::game(input, false).map_err(|e| ParseError::from_peg(6, input, e))
 }
 
-#[aoc(day6, part1)]
-fn day6_part1(games: &[Race]) -> usize {
+pub fn parse_day6_part2(input: &str) -> Result<Race, ParseError> {
+    game_parser::game(input, true)
+        .map(|races| races[0])
+        .map_err(|e| ParseError::from_peg(6, input, e))
+}
+
+pub fn day6_part1(games: &[Race]) -> Result<usize, Overflow> {
     games
         .iter()
-        .map(|g| {
-            (0..g.time.as_millis())
-                .map(|dt| (dt * (g.time.as_millis() - dt) > g.distance as u128) as usize)
-                .sum::<usize>()
-        })
-        .product()
+        .try_fold(1_usize, |acc, r| acc.checked_mul(r.ways()).ok_or(Overflow))
 }
 
-#[aoc(day6, part2)]
-fn day6_part2(_: &[Race]) -> usize {
-    day6_part1(&[Race {
-        time: Duration::from_millis(50748685),
-        distance: 242101716911252,
-    }])
+pub fn day6_part2(race: &Race) -> usize {
+    race.ways()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
     fn test_part1() {
         let input = ["Time:      7  15   30", "Distance:  9  40  200"].join("\n");
-        assert_eq!(day6_part1(&parse_day6(&input)), 288);
+        assert_eq!(day6_part1(&parse_day6(&input).unwrap()), Ok(288));
+    }
+
+    #[test]
+    fn test_part1_overflow() {
+        let input = format!("Time: {0} {0}\nDistance: 0 0", 1_u64 << 40);
+        assert_eq!(day6_part1(&parse_day6(&input).unwrap()), Err(Overflow));
+    }
+
+    #[test]
+    fn test_part2() {
+        let input = ["Time:      7  15   30", "Distance:  9  40  200"].join("\n");
+        assert_eq!(day6_part2(&parse_day6_part2(&input).unwrap()), 71503);
+    }
+
+    #[test]
+    fn test_parse_ignoring_kerning() {
+        let input = ["Time:      7  15   30", "Distance:  9  40  200"].join("\n");
+        assert_eq!(parse_day6_part2(&input), Ok(Race::from((71530, 940200))));
+    }
+
+    #[test]
+    fn test_parse_mismatched_counts() {
+        let input = ["Time:      7  15   30", "Distance:  9  40"].join("\n");
+
+        let err = parse_day6(&input).unwrap_err();
+        assert_eq!(err.line, 2);
+        assert_eq!(err.snippet, "Distance:  9  40");
+        assert!(err.expected.contains("as many distances as times"));
+        assert_eq!(parse_day6_part2(&input).unwrap_err(), err);
+    }
+
+    fn brute_force(race: &Race) -> Vec<u128> {
+        let t = race.time.as_millis();
+
+        (0..t)
+            .filter(|dt| dt * (t - dt) > race.distance as u128)
+            .collect()
+    }
+
+    #[test]
+    fn test_winning() {
+        assert_eq!(Race::from((7, 9)).winning(), Some(2..=5));
+        assert_eq!(Race::from((30, 200)).winning(), Some(11..=19));
+        assert_eq!(Race::from((10, 25)).winning(), None);
+        assert_eq!(Race::from((10, 24)).winning(), Some(5..=5));
+        assert_eq!(Race::from((0, 0)).winning(), None);
+        assert_eq!(Race::from((1, 0)).winning(), None);
+        assert_eq!(Race::from((2, 0)).winning(), Some(1..=1));
+    }
+
+    #[test]
+    fn test_winning_large() {
+        let race = Race::from((50748685, 242101716911252));
+
+        assert_eq!(race.ways(), 40087680);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_winning_matches_brute_force(t in 0..300_usize, d in 0..25_000_usize) {
+            let race = Race::from((t, d));
+            let expected = brute_force(&race);
+
+            let winning = race.winning().map(|w| w.collect::<Vec<_>>());
+
+            prop_assert_eq!(winning.unwrap_or_default(), expected);
+        }
+    }
+
+    #[test]
+    fn test_parse_too_large() {
+        let input = ["Time: 7 1", "Distance: 9 99999999999999999999"].join("\n");
+        let err = parse_day6(&input).unwrap_err();
+        assert!(err.expected.contains("usize"));
 
-        let input = ["Time:      71530", "Distance:  940200"].join("\n");
-        assert_eq!(day6_part1(&parse_day6(&input)), 71503);
+        let input = ["Time: 9999999999 9999999999", "Distance: 9 1"].join("\n");
+        let err = parse_day6_part2(&input).unwrap_err();
+        assert!(err.expected.contains("usize"));
     }
 }
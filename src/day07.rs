@@ -1,11 +1,15 @@
 use crate::day07::CardType::{
     FiveOfKind, FourOfKind, FullHouse, HighCard, OnePair, ThreeOfKind, TwoPair,
 };
-use aoc_runner_derive::{aoc, aoc_generator};
+use crate::error::{Overflow, ParseError};
+use crate::stream::{for_each_line, StreamError};
 use itertools::Itertools;
-use std::cmp::Ordering;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BTreeMap;
+use std::io::BufRead;
+use std::iter::repeat_n;
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd, Debug)]
 pub enum Card {
     Ace,
     King,
@@ -20,32 +24,33 @@ pub enum Card {
     Four,
     Three,
     Two,
-    SneakyJoker,
 }
 
-impl From<&str> for Card {
-    fn from(value: &str) -> Self {
+impl TryFrom<&str> for Card {
+    type Error = &'static str;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
         match value {
-            "A" => Self::Ace,
-            "K" => Self::King,
-            "Q" => Self::Queen,
-            "J" => Self::Joker,
-            "T" => Self::T,
-            "9" => Self::Nine,
-            "8" => Self::Eight,
-            "7" => Self::Seven,
-            "6" => Self::Six,
-            "5" => Self::Five,
-            "4" => Self::Four,
-            "3" => Self::Three,
-            "2" => Self::Two,
-            _ => unreachable!(),
+            "A" => Ok(Self::Ace),
+            "K" => Ok(Self::King),
+            "Q" => Ok(Self::Queen),
+            "J" => Ok(Self::Joker),
+            "T" => Ok(Self::T),
+            "9" => Ok(Self::Nine),
+            "8" => Ok(Self::Eight),
+            "7" => Ok(Self::Seven),
+            "6" => Ok(Self::Six),
+            "5" => Ok(Self::Five),
+            "4" => Ok(Self::Four),
+            "3" => Ok(Self::Three),
+            "2" => Ok(Self::Two),
+            _ => Err("card"),
         }
     }
 }
 
-#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
-enum CardType {
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug)]
+pub(crate) enum CardType {
     FiveOfKind,
     FourOfKind,
     FullHouse,
@@ -55,24 +60,117 @@ enum CardType {
     HighCard,
 }
 
-#[derive(Eq, PartialEq, Clone)]
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum TieBreak {
+    /// Compare cards one by one in the order they were dealt, as Camel Cards does.
+    Positional,
+    /// Compare the biggest groups first and the highest cards within them, as poker does.
+    HighCard,
+}
+
+/// RuleSet decides how hands are ranked against each other.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct RuleSet {
+    /// Every card, strongest first.
+    order: Vec<Card>,
+    /// Cards that count as whatever makes the best hand.
+    wild: Vec<Card>,
+    tie_break: TieBreak,
+}
+
+impl RuleSet {
+    pub fn new(order: Vec<Card>, wild: Vec<Card>, tie_break: TieBreak) -> Self {
+        Self {
+            order,
+            wild,
+            tie_break,
+        }
+    }
+
+    pub fn camel_cards() -> Self {
+        use Card::*;
+
+        Self::new(
+            vec![
+                Ace, King, Queen, Joker, T, Nine, Eight, Seven, Six, Five, Four, Three, Two,
+            ],
+            vec![],
+            TieBreak::Positional,
+        )
+    }
+
+    /// jokers is Camel Cards where jokers are wild but the weakest card on their own.
+    pub fn jokers() -> Self {
+        use Card::*;
+
+        Self::new(
+            vec![
+                Ace, King, Queen, T, Nine, Eight, Seven, Six, Five, Four, Three, Two, Joker,
+            ],
+            vec![Joker],
+            TieBreak::Positional,
+        )
+    }
+
+    /// strength grows with how strong the card is; cards missing from the order are weakest.
+    fn strength(&self, card: &Card) -> usize {
+        self.order.len()
+            - self
+                .order
+                .iter()
+                .position(|c| c == card)
+                .unwrap_or(self.order.len())
+    }
+
+    /// compare orders hands from weakest to strongest.
+    fn compare(&self, h1: &Hand, h2: &Hand) -> Ordering {
+        self.sort_key(h1).cmp(&self.sort_key(h2))
+    }
+
+    /// sort_key orders hands the way compare does.
+    pub(crate) fn sort_key(&self, hand: &Hand) -> (Reverse<CardType>, Vec<usize>) {
+        // CardType lists the strongest type first.
+        (Reverse(hand.cards_type(self)), self.tie_break_key(hand))
+    }
+
+    fn tie_break_key(&self, hand: &Hand) -> Vec<usize> {
+        match self.tie_break {
+            TieBreak::Positional => hand.cards.iter().map(|c| self.strength(c)).collect(),
+            TieBreak::HighCard => hand
+                .cards
+                .iter()
+                .map(|c| self.strength(c))
+                .counts()
+                .into_iter()
+                .sorted_by_key(|&(strength, count)| Reverse((count, strength)))
+                .flat_map(|(strength, count)| repeat_n(strength, count))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Eq, PartialEq, Clone, Debug)]
 pub struct Hand {
     cards: Vec<Card>,
-    bid: usize,
+    pub(crate) bid: usize,
 }
 
 impl Hand {
-    fn cards_type(&self) -> CardType {
+    fn cards_type(&self, rules: &RuleSet) -> CardType {
         let mut counts = self.cards.iter().counts();
 
-        let sneaky_jokers = counts.remove(&Card::SneakyJoker).unwrap_or(0);
+        let wild = rules
+            .wild
+            .iter()
+            .map(|c| counts.remove(c).unwrap_or(0))
+            .sum();
 
         let mut counts = counts.values().sorted().cloned().rev().collect::<Vec<_>>();
 
-        if sneaky_jokers == 5 {
-            counts.push(sneaky_jokers)
+        if counts.is_empty() {
+            counts.push(wild)
         } else {
-            counts[0] += sneaky_jokers;
+            counts[0] += wild;
         }
 
         match counts.len() {
@@ -101,45 +199,89 @@ peg::parser! {
 
     pub rule sep() -> String = n:$([' ']+) { n.to_string() }
 
-    pub rule card() -> Card = c:$(['2'..='9'|'A'|'K'|'Q'|'J'|'T']) { Card::from(c) }
+    pub rule card() -> Card = c:$(['2'..='9'|'A'|'K'|'Q'|'J'|'T']) {? Card::try_from(c) }
 
-    pub rule hand() -> Hand = cards:(card()*) sep() bid:(number()) { Hand{cards, bid} }
+    pub rule hand() -> Hand = cards:(card()*<5>) sep() bid:(number()) { Hand{cards, bid} }
 
     pub rule input() -> Vec<Hand> = hands:(hand() ** "\n")
   }
 }
 
-#[aoc_generator(day7)]
-fn parse_day7(input: &str) -> Vec<Hand> {
-    input_parser::input(input).unwrap()
+pub fn parse_day7(input: &str) -> Result<Vec<Hand>, ParseError> {
+    input_parser::input(input).map_err(|e| ParseError::from_peg(7, input, e))
+}
+
+pub fn day7_part1(hands: &[Hand]) -> Result<usize, Overflow> {
+    winnings(hands, &RuleSet::camel_cards())
 }
 
-#[aoc(day7, part1)]
-fn day7_part1(hands: &[Hand]) -> usize {
+pub fn day7_part2(hands: &[Hand]) -> Result<usize, Overflow> {
+    winnings(hands, &RuleSet::jokers())
+}
+
+pub fn winnings(hands: &[Hand], rules: &RuleSet) -> Result<usize, Overflow> {
     hands
         .iter()
-        .cloned()
-        .sorted_by(|h1, h2| match h1.cards_type().cmp(&h2.cards_type()) {
-            Ordering::Equal => h1.cards.cmp(&h2.cards),
-            whatever => whatever,
+        .sorted_by(|h1, h2| rules.compare(h1, h2))
+        .zip(1_usize..)
+        .try_fold(0_usize, |acc, (h, r)| {
+            r.checked_mul(h.bid)
+                .and_then(|w| acc.checked_add(w))
+                .ok_or(Overflow)
         })
-        .rev()
-        .zip(1..)
-        .fold(0, |acc, (h, r)| acc + r * h.bid)
-}
-
-#[aoc(day7, part2)]
-fn day7_part2(hands: &[Hand]) -> usize {
-    let mut hands = hands.to_vec();
-    for hand in hands.iter_mut() {
-        for i in hand.cards.iter_mut() {
-            if *i == Card::Joker {
-                *i = Card::SneakyJoker
-            }
-        }
+}
+
+/// day7_part1_stream is day7_part1 reading one hand at a time.
+pub fn day7_part1_stream(input: impl BufRead) -> Result<usize, StreamError> {
+    winnings_stream(input, &RuleSet::camel_cards())
+}
+
+/// day7_part2_stream is day7_part2 reading one hand at a time.
+pub fn day7_part2_stream(input: impl BufRead) -> Result<usize, StreamError> {
+    winnings_stream(input, &RuleSet::jokers())
+}
+
+/// winnings_stream is winnings without holding every hand. Hands are grouped by how they sort,
+/// and there are only so many ways five cards can sort, so memory stays bounded. Equal hands
+/// keep their input order, so a group of `count` hands ranked from `base + 1` up wins
+/// `(base + 1) * bids + weighted`, where `weighted` sums each bid times the hands before it
+/// in the group.
+pub fn winnings_stream(input: impl BufRead, rules: &RuleSet) -> Result<usize, StreamError> {
+    #[derive(Default)]
+    struct G {
+        count: usize,
+        bids: usize,
+        weighted: usize,
     }
 
-    day7_part1(&hands)
+    let mut groups: BTreeMap<_, G> = BTreeMap::new();
+    for_each_line(input, |line, l| {
+        let hand =
+            input_parser::hand(l).map_err(|e| ParseError::from_peg(7, l, e).on_line(line))?;
+        let g = groups.entry(rules.sort_key(&hand)).or_default();
+        g.weighted = g
+            .count
+            .checked_mul(hand.bid)
+            .and_then(|w| g.weighted.checked_add(w))
+            .ok_or(Overflow)?;
+        g.bids = g.bids.checked_add(hand.bid).ok_or(Overflow)?;
+        g.count += 1;
+        Ok(())
+    })?;
+
+    let mut base: usize = 0;
+    let total = groups.values().try_fold(0_usize, |acc, g| {
+        let total = base
+            .checked_add(1)
+            .and_then(|r| r.checked_mul(g.bids))
+            .and_then(|b| b.checked_add(g.weighted))
+            .and_then(|w| acc.checked_add(w))
+            .ok_or(Overflow)?;
+        base += g.count;
+        Ok::<_, Overflow>(total)
+    })?;
+
+    Ok(total)
 }
 
 #[cfg(test)]
@@ -156,7 +298,8 @@ mod tests {
             "QQQJA 483",
         ]
         .join("\n");
-        assert_eq!(day7_part1(&parse_day7(&input)), 6440);
+        assert_eq!(day7_part1(&parse_day7(&input).unwrap()), Ok(6440));
+        assert_eq!(day7_part1_stream(input.as_bytes()).unwrap(), 6440);
     }
 
     #[test]
@@ -169,6 +312,75 @@ mod tests {
             "QQQJA 483",
         ]
         .join("\n");
-        assert_eq!(day7_part2(&parse_day7(&input)), 5905);
+        assert_eq!(day7_part2(&parse_day7(&input).unwrap()), Ok(5905));
+        assert_eq!(day7_part2_stream(input.as_bytes()).unwrap(), 5905);
+    }
+
+    #[test]
+    fn test_cards_type_with_wilds() {
+        let hands = parse_day7(&["JJJJJ 1", "KTJJT 1", "2345J 1"].join("\n")).unwrap();
+
+        assert_eq!(
+            hands
+                .iter()
+                .map(|h| h.cards_type(&RuleSet::jokers()))
+                .collect::<Vec<_>>(),
+            vec![FiveOfKind, FourOfKind, OnePair]
+        );
+        assert_eq!(
+            hands
+                .iter()
+                .map(|h| h.cards_type(&RuleSet::camel_cards()))
+                .collect::<Vec<_>>(),
+            vec![FiveOfKind, TwoPair, HighCard]
+        );
+    }
+
+    #[test]
+    fn test_high_card_tie_break() {
+        let hands = parse_day7(&["22AAA 1", "KKKQQ 2"].join("\n")).unwrap();
+
+        let positional = RuleSet::camel_cards();
+        assert_eq!(positional.compare(&hands[0], &hands[1]), Ordering::Less);
+
+        let poker = RuleSet {
+            tie_break: TieBreak::HighCard,
+            ..positional
+        };
+        assert_eq!(poker.compare(&hands[0], &hands[1]), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_deuces_wild() {
+        let deuces = RuleSet {
+            wild: vec![Card::Two],
+            ..RuleSet::camel_cards()
+        };
+        let input = [
+            "32T3K 765",
+            "T55J5 684",
+            "KK677 28",
+            "KTJJT 220",
+            "QQQJA 483",
+            "2222A 1",
+        ];
+        let hands = parse_day7(&input.join("\n")).unwrap();
+
+        assert_eq!(hands[5].cards_type(&deuces), FiveOfKind);
+        assert_eq!(winnings(&hands, &deuces), Ok(7728));
+        assert_eq!(
+            winnings_stream(input.join("\n").as_bytes(), &deuces).unwrap(),
+            7728
+        );
+    }
+
+    #[test]
+    fn test_overflow() {
+        let input = ["32T3K 1".to_string(), format!("T55J5 {}", usize::MAX)].join("\n");
+        assert_eq!(day7_part1(&parse_day7(&input).unwrap()), Err(Overflow));
+        assert!(matches!(
+            day7_part1_stream(input.as_bytes()),
+            Err(StreamError::Solve(_))
+        ));
     }
 }
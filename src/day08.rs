@@ -1,28 +1,22 @@
 use crate::day08::Direction::{Left, Right};
-use aoc_runner_derive::{aoc, aoc_generator};
-use itertools::FoldWhile::{Continue, Done};
-use itertools::{max, Itertools};
-use std::collections::HashMap;
+use crate::error::ParseError;
+use crate::par;
+use itertools::Itertools;
+use num_bigint::BigInt;
+use num_traits::{Euclid, One, Zero};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
 use std::ops::Deref;
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd, Debug)]
 pub enum Direction {
     Left,
     Right,
 }
 
-impl From<char> for Direction {
-    fn from(value: char) -> Self {
-        match value {
-            'L' => Left,
-            'R' => Right,
-            _ => unreachable!(),
-        }
-    }
-}
-
 #[derive(Eq, PartialEq, Hash, Clone, Debug)]
-pub struct Location(String);
+pub struct Location(pub String);
 
 impl Deref for Location {
     type Target = String;
@@ -32,13 +26,14 @@ impl Deref for Location {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct Instruction {
     from: Location,
     left: Location,
     right: Location,
 }
 
+#[derive(Debug)]
 pub struct Map {
     directions: Vec<Direction>,
     instructions: Vec<Instruction>,
@@ -66,84 +61,309 @@ impl Map {
         }
     }
 
-    fn get(&self, location: &Location) -> &Instruction {
-        &self.hash_map[location]
+    /// walk returns the steps taken from `start` until reaching an end node, or until the walk
+    /// starts repeating itself if it never does.
+    pub fn walk(&self, start: &Location, is_end: impl Fn(&Location) -> bool) -> Vec<Step<'_>> {
+        let mut seen = HashSet::new();
+        let mut steps = vec![];
+
+        let Some((mut location, _)) = self.hash_map.get_key_value(start) else {
+            return steps;
+        };
+        for idx in (0..self.directions.len()).cycle() {
+            if is_end(location) || !seen.insert((location, idx)) {
+                break;
+            }
+
+            let direction = self.directions[idx];
+            steps.push((location, direction));
+            location = self.transition(location, &direction);
+        }
+
+        steps
+    }
+
+    pub fn to_dot(&self) -> String {
+        self.to_dot_with_path(&[])
+    }
+
+    /// to_dot_with_path renders the network in GraphViz DOT, drawing the edges taken by `path`
+    /// in bold. Nodes and edges follow input order so the output is stable.
+    pub fn to_dot_with_path(&self, path: &[Step]) -> String {
+        let taken = path.iter().copied().collect::<HashSet<_>>();
+
+        let mut dot = vec![
+            "digraph network {".to_string(),
+            "  node [shape=box];".to_string(),
+        ];
+
+        for i in self.instructions.iter() {
+            let fill = match i.from.chars().last() {
+                Some('A') => ", style=filled, fillcolor=palegreen",
+                Some('Z') => ", style=filled, fillcolor=lightcoral",
+                _ => "",
+            };
+            let visited = if path.iter().any(|(l, _)| **l == i.from) {
+                ", penwidth=2"
+            } else {
+                ""
+            };
+
+            dot.push(format!(
+                "  \"{}\" [label=\"{}\"{}{}];",
+                *i.from, *i.from, fill, visited
+            ));
+        }
+
+        for i in self.instructions.iter() {
+            for (direction, to, label) in [(Left, &i.left, "L"), (Right, &i.right, "R")] {
+                let style = if taken.contains(&(&i.from, direction)) {
+                    ", color=blue, penwidth=2"
+                } else {
+                    ""
+                };
+
+                dot.push(format!(
+                    "  \"{}\" -> \"{}\" [label=\"{}\"{}];",
+                    *i.from, **to, label, style
+                ));
+            }
+        }
+
+        dot.push("}".to_string());
+        dot.join("\n")
     }
 }
 
+/// Step is a location left during a walk and the direction it was left by.
+pub type Step<'a> = (&'a Location, Direction);
+
 peg::parser! {
   grammar input_parser() for str {
     pub rule character() -> char = c:(['A'..='Z'|'0'..='9'])
 
-    pub rule direction() -> Direction = c:(character()) { Direction::from(c) }
+    pub rule direction() -> Direction = "L" { Left } / "R" { Right }
 
     pub rule location() -> Location = c:(character()*<3>) {Location(c.iter().join(""))}
 
     pub rule instruction() -> Instruction = from:(location()) " = (" left:(location()) ", " right:(location()) ")" {Instruction{from, left, right}}
 
-    pub rule map() -> Map = directions:(direction()*) "\n\n" instructions:(instruction() ** "\n") {Map::new(directions, instructions)}
+    pub rule map() -> Map = directions:(direction()+) "\n\n" instructions:(instruction() ** "\n") {Map::new(directions, instructions)}
   }
 }
 
-#[aoc_generator(day8)]
-fn parse_day8(input: &str) -> Map {
-    input_parser::map(input).unwrap()
+pub fn parse_day8(input: &str) -> Result<Map, ParseError> {
+    let map = input_parser::map(input).map_err(|e| ParseError::from_peg(8, input, e))?;
+
+    // Every location needs exactly one instruction, and every location an instruction leads to
+    // needs one too. The grammar fixes the layout of each line, so the left and right locations
+    // sit at columns 7 and 12.
+    let mut defined = HashSet::new();
+    let mut offset = input.lines().take(2).map(|l| l.len() + 1).sum::<usize>();
+    for (instruction, line) in map.instructions.iter().zip(input.lines().skip(2)) {
+        if !defined.insert(&instruction.from) {
+            return Err(ParseError::new(
+                8,
+                input,
+                offset,
+                ["a location not defined before"],
+            ));
+        }
+
+        for (column, to) in [(7, &instruction.left), (12, &instruction.right)] {
+            if !map.hash_map.contains_key(to) {
+                return Err(ParseError::new(
+                    8,
+                    input,
+                    offset + column,
+                    ["a defined location"],
+                ));
+            }
+        }
+
+        offset += line.len() + 1;
+    }
+
+    Ok(map)
 }
 
-#[aoc(day8, part1)]
-fn day8_part1(map: &Map) -> usize {
-    let location = Location("AAA".to_string());
-    let destination = Location("ZZZ".to_string());
+/// day8_part1 follows the directions from AAA, failing if they never lead to ZZZ.
+pub fn day8_part1(map: &Map) -> Result<usize, NeverArrives> {
+    let start = Location("AAA".to_string());
+    let steps = map.walk(&start, |l| **l == "ZZZ");
 
-    map.directions
-        .iter()
-        .cycle()
-        .fold_while((0, &location), |(count, location), dir| match location {
-            l if l.eq(&destination) => Done((count, l)),
-            _ => Continue((count + 1, map.transition(location, dir))),
-        })
-        .into_inner()
-        .0
+    match steps.last() {
+        Some((l, d)) if **map.transition(l, d) == "ZZZ" => Ok(steps.len()),
+        _ => Err(NeverArrives),
+    }
 }
 
-#[aoc(day8, part2)]
-fn day8_part2(map: &Map) -> usize {
-    map.instructions
+pub fn day8_part2(map: &Map) -> Result<BigInt, NeverSynchronizes> {
+    let starts = map
+        .instructions
         .iter()
         .filter(|i| i.from.ends_with('A'))
-        .map(|ins| {
-            map.directions
-                .iter()
-                .cycle()
-                .fold_while((0, &ins.from), |(count, location), dir| match location {
-                    l if l.ends_with('Z') => Done((count, l)),
-                    _ => Continue((count + 1, map.transition(location, dir))),
-                })
-                .into_inner()
-                .0
-        })
-        .fold(1, lcm)
+        .collect::<Vec<_>>();
+    if starts.is_empty() {
+        return Err(NeverSynchronizes::NoGhosts);
+    }
+    let cycles = par::map(&starts, |ins| map.cycle(&ins.from, |l| l.ends_with('Z')));
+
+    synchronize(&cycles).ok_or(NeverSynchronizes::Apart)
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct NeverArrives;
+
+impl fmt::Display for NeverArrives {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the directions never lead from AAA to ZZZ")
+    }
 }
 
-fn lcm(first: usize, second: usize) -> usize {
-    first * second / gcd(first, second)
+impl Error for NeverArrives {}
+
+/// NeverSynchronizes is why day8_part2 has no answer.
+#[derive(Debug, Eq, PartialEq)]
+pub enum NeverSynchronizes {
+    /// There is no ..A node to start a ghost from.
+    NoGhosts,
+    /// The ghosts never all stand on a ..Z node at once.
+    Apart,
 }
 
-fn gcd(first: usize, second: usize) -> usize {
-    let mut max = first;
-    let mut min = second;
-    if min > max {
-        std::mem::swap(&mut max, &mut min);
+impl fmt::Display for NeverSynchronizes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NeverSynchronizes::NoGhosts => write!(f, "there is no ..A node to start a ghost from"),
+            NeverSynchronizes::Apart => {
+                write!(f, "the ghosts never all stand on a ..Z node at once")
+            }
+        }
     }
+}
+
+impl Error for NeverSynchronizes {}
+
+/// Cycle records when a walk stands on an end node. Since the walk is deterministic over
+/// (location, direction index) states, it is a prefix of `offset` steps followed by a loop of
+/// `length` steps repeating forever.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Cycle {
+    offset: usize,
+    length: usize,
+    /// Steps before the loop at which the walk is on an end node.
+    prefix_hits: Vec<usize>,
+    /// Steps within the first lap of the loop at which the walk is on an end node.
+    cycle_hits: Vec<usize>,
+}
+
+impl Cycle {
+    fn hits(&self, t: usize) -> bool {
+        if t < self.offset {
+            self.prefix_hits.binary_search(&t).is_ok()
+        } else {
+            let t = self.offset + (t - self.offset) % self.length;
+            self.cycle_hits.binary_search(&t).is_ok()
+        }
+    }
+}
+
+impl Map {
+    fn cycle(&self, start: &Location, is_end: impl Fn(&Location) -> bool) -> Cycle {
+        let mut seen = HashMap::new();
+        let mut hits = vec![];
 
-    loop {
-        let res = max % min;
-        if res == 0 {
-            return min;
+        let mut location = start;
+        for t in 0.. {
+            let idx = t % self.directions.len();
+
+            if let Some(&offset) = seen.get(&(location, idx)) {
+                let (prefix_hits, cycle_hits) = hits.into_iter().partition(|&h| h < offset);
+
+                return Cycle {
+                    offset,
+                    length: t - offset,
+                    prefix_hits,
+                    cycle_hits,
+                };
+            }
+
+            seen.insert((location, idx), t);
+            if is_end(location) {
+                hits.push(t);
+            }
+
+            location = self.transition(location, &self.directions[idx]);
         }
 
-        max = min;
-        min = res;
+        unreachable!()
+    }
+}
+
+/// synchronize returns the first step at which every cycle is on an end node at once. Loop
+/// lengths multiply together, so the step is counted in a BigInt.
+fn synchronize(cycles: &[Cycle]) -> Option<BigInt> {
+    let settled = cycles.iter().map(|c| c.offset).max().unwrap_or(0);
+
+    // Until every walk has entered its loop, the one with the longest prefix is still in it,
+    // so the answer can only be one of its prefix hits.
+    if let Some(slowest) = cycles.iter().max_by_key(|c| c.offset) {
+        for &t in slowest.prefix_hits.iter() {
+            if cycles.iter().all(|c| c.hits(t)) {
+                return Some(t.into());
+            }
+        }
+    }
+
+    // From then on each walk hits on fixed residues modulo its loop length.
+    let residues = cycles
+        .iter()
+        .fold(vec![(BigInt::zero(), BigInt::one())], |acc, c| {
+            acc.iter()
+                .cartesian_product(c.cycle_hits.iter())
+                .filter_map(|(r, &h)| crt(r.clone(), ((h % c.length).into(), c.length.into())))
+                .unique()
+                .collect()
+        });
+
+    let settled = BigInt::from(settled);
+    residues
+        .into_iter()
+        .map(|(r, modulus)| {
+            if r >= settled {
+                r
+            } else {
+                let laps = (&settled - &r + &modulus - 1) / &modulus;
+                r + laps * modulus
+            }
+        })
+        .min()
+}
+
+/// crt solves `t ≡ a1 (mod n1)` and `t ≡ a2 (mod n2)` for moduli that need not be coprime,
+/// returning the combined residue and modulus.
+fn crt((a1, n1): (BigInt, BigInt), (a2, n2): (BigInt, BigInt)) -> Option<(BigInt, BigInt)> {
+    let (g, p, _) = extended_gcd(&n1, &n2);
+    if !((&a2 - &a1) % &g).is_zero() {
+        return None;
+    }
+
+    let modulus = &n1 / &g * &n2;
+    let m = &n2 / &g;
+    let k = ((&a2 - &a1) / &g % &m * p).rem_euclid(&m);
+
+    Some(((a1 + n1 * k).rem_euclid(&modulus), modulus))
+}
+
+/// extended_gcd returns `(g, x, y)` such that `a * x + b * y = g = gcd(a, b)`.
+fn extended_gcd(a: &BigInt, b: &BigInt) -> (BigInt, BigInt, BigInt) {
+    if b.is_zero() {
+        (a.clone(), BigInt::one(), BigInt::zero())
+    } else {
+        let (g, x, y) = extended_gcd(b, &(a % b));
+        let z = x - a / b * &y;
+        (g, y, z)
     }
 }
 
@@ -165,7 +385,57 @@ mod tests {
             "ZZZ = (ZZZ, ZZZ)",
         ]
         .join("\n");
-        assert_eq!(day8_part1(&parse_day8(&input)), 2);
+        assert_eq!(day8_part1(&parse_day8(&input).unwrap()), Ok(2));
+    }
+
+    #[test]
+    fn test_part1_never_arrives() {
+        let input = [
+            "L",
+            "",
+            "AAA = (BBB, BBB)",
+            "BBB = (AAA, ZZZ)",
+            "ZZZ = (ZZZ, ZZZ)",
+        ]
+        .join("\n");
+        assert_eq!(day8_part1(&parse_day8(&input).unwrap()), Err(NeverArrives));
+
+        let input = ["L", "", "BBB = (BBB, BBB)"].join("\n");
+        assert_eq!(day8_part1(&parse_day8(&input).unwrap()), Err(NeverArrives));
+    }
+
+    #[test]
+    fn test_parse_undefined_location() {
+        let input = [
+            "LR",
+            "",
+            "AAA = (BBB, ZZZ)",
+            "BBB = (AAA, CCC)",
+            "ZZZ = (ZZZ, ZZZ)",
+        ]
+        .join("\n");
+        let err = parse_day8(&input).unwrap_err();
+
+        assert_eq!((err.line, err.column), (4, 13));
+        assert_eq!(err.snippet, "BBB = (AAA, CCC)");
+    }
+
+    #[test]
+    fn test_parse_duplicate_location() {
+        let input = [
+            "LR",
+            "",
+            "AAA = (BBB, ZZZ)",
+            "BBB = (AAA, ZZZ)",
+            "AAA = (ZZZ, ZZZ)",
+            "ZZZ = (ZZZ, ZZZ)",
+        ]
+        .join("\n");
+        let err = parse_day8(&input).unwrap_err();
+
+        assert_eq!((err.line, err.column), (5, 1));
+        assert_eq!(err.snippet, "AAA = (ZZZ, ZZZ)");
+        assert!(err.expected.contains("a location not defined before"));
     }
 
     #[test]
@@ -183,6 +453,181 @@ mod tests {
             "XXX = (XXX, XXX)",
         ]
         .join("\n");
-        assert_eq!(day8_part2(&parse_day8(&input)), 6);
+        assert_eq!(day8_part2(&parse_day8(&input).unwrap()), Ok(6.into()));
+    }
+
+    #[test]
+    fn test_part2_with_prefix_and_offsets() {
+        let input = [
+            "L",
+            "",
+            "11A = (11B, 11B)",
+            "11B = (11Z, 11Z)",
+            "11Z = (11B, 11B)",
+            "22A = (22Z, 22Z)",
+            "22Z = (22C, 22C)",
+            "22C = (22D, 22D)",
+            "22D = (22Z, 22Z)",
+        ]
+        .join("\n");
+        let map = parse_day8(&input).unwrap();
+
+        assert_eq!(
+            map.cycle(&Location("11A".to_string()), |l| l.ends_with('Z')),
+            Cycle {
+                offset: 1,
+                length: 2,
+                prefix_hits: vec![],
+                cycle_hits: vec![2],
+            }
+        );
+        assert_eq!(day8_part2(&map), Ok(4.into()));
+    }
+
+    #[test]
+    fn test_part2_never_synchronizes() {
+        let input = [
+            "L",
+            "",
+            "11A = (11B, 11B)",
+            "11B = (11Z, 11Z)",
+            "11Z = (11B, 11B)",
+            "22A = (22Z, 22Z)",
+            "22Z = (22B, 22B)",
+            "22B = (22Z, 22Z)",
+        ]
+        .join("\n");
+        assert_eq!(
+            day8_part2(&parse_day8(&input).unwrap()),
+            Err(NeverSynchronizes::Apart)
+        );
+    }
+
+    #[test]
+    fn test_part2_no_ghosts() {
+        let input = ["L", "", "BBB = (ZZZ, ZZZ)", "ZZZ = (ZZZ, ZZZ)"].join("\n");
+        assert_eq!(
+            day8_part2(&parse_day8(&input).unwrap()),
+            Err(NeverSynchronizes::NoGhosts)
+        );
+    }
+
+    #[test]
+    fn test_part2_hit_in_prefix() {
+        let input = [
+            "L",
+            "",
+            "11A = (11Z, 11Z)",
+            "11Z = (11B, 11B)",
+            "11B = (11B, 11B)",
+            "22A = (22Z, 22Z)",
+            "22Z = (22Z, 22Z)",
+        ]
+        .join("\n");
+        assert_eq!(day8_part2(&parse_day8(&input).unwrap()), Ok(1.into()));
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let input = [
+            "LR",
+            "",
+            "11A = (11B, 11A)",
+            "11B = (11A, 11Z)",
+            "11Z = (11B, 11A)",
+        ]
+        .join("\n");
+        let map = parse_day8(&input).unwrap();
+
+        assert_eq!(
+            map.to_dot(),
+            [
+                "digraph network {",
+                "  node [shape=box];",
+                "  \"11A\" [label=\"11A\", style=filled, fillcolor=palegreen];",
+                "  \"11B\" [label=\"11B\"];",
+                "  \"11Z\" [label=\"11Z\", style=filled, fillcolor=lightcoral];",
+                "  \"11A\" -> \"11B\" [label=\"L\"];",
+                "  \"11A\" -> \"11A\" [label=\"R\"];",
+                "  \"11B\" -> \"11A\" [label=\"L\"];",
+                "  \"11B\" -> \"11Z\" [label=\"R\"];",
+                "  \"11Z\" -> \"11B\" [label=\"L\"];",
+                "  \"11Z\" -> \"11A\" [label=\"R\"];",
+                "}",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_to_dot_with_path() {
+        let input = [
+            "RL",
+            "",
+            "AAA = (BBB, CCC)",
+            "BBB = (AAA, BBB)",
+            "CCC = (ZZZ, AAA)",
+            "ZZZ = (ZZZ, ZZZ)",
+        ]
+        .join("\n");
+        let map = parse_day8(&input).unwrap();
+        let path = map.walk(&Location("AAA".to_string()), |l| **l == "ZZZ");
+
+        assert_eq!(Ok(path.len()), day8_part1(&map));
+        assert_eq!(
+            map.to_dot_with_path(&path),
+            [
+                "digraph network {",
+                "  node [shape=box];",
+                "  \"AAA\" [label=\"AAA\", style=filled, fillcolor=palegreen, penwidth=2];",
+                "  \"BBB\" [label=\"BBB\"];",
+                "  \"CCC\" [label=\"CCC\", penwidth=2];",
+                "  \"ZZZ\" [label=\"ZZZ\", style=filled, fillcolor=lightcoral];",
+                "  \"AAA\" -> \"BBB\" [label=\"L\"];",
+                "  \"AAA\" -> \"CCC\" [label=\"R\", color=blue, penwidth=2];",
+                "  \"BBB\" -> \"AAA\" [label=\"L\"];",
+                "  \"BBB\" -> \"BBB\" [label=\"R\"];",
+                "  \"CCC\" -> \"ZZZ\" [label=\"L\", color=blue, penwidth=2];",
+                "  \"CCC\" -> \"AAA\" [label=\"R\"];",
+                "  \"ZZZ\" -> \"ZZZ\" [label=\"L\"];",
+                "  \"ZZZ\" -> \"ZZZ\" [label=\"R\"];",
+                "}",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_crt() {
+        let crt = |(a1, n1): (i64, i64), (a2, n2): (i64, i64)| {
+            crt((a1.into(), n1.into()), (a2.into(), n2.into()))
+        };
+        let some = |r: i64, n: i64| Some((r.into(), n.into()));
+
+        assert_eq!(crt((2, 3), (3, 5)), some(8, 15));
+        assert_eq!(crt((0, 4), (2, 6)), some(8, 12));
+        assert_eq!(crt((1, 4), (2, 6)), None);
+        assert_eq!(crt((0, 1), (5, 7)), some(5, 7));
+    }
+
+    #[test]
+    fn test_crt_beyond_i128() {
+        let n1 = BigInt::from(i128::MAX);
+        let n2: BigInt = &n1 - 1;
+
+        assert_eq!(
+            crt((BigInt::zero(), n1.clone()), (BigInt::zero(), n2.clone())),
+            Some((BigInt::zero(), n1 * n2))
+        );
+    }
+
+    #[test]
+    fn test_parse_bad_direction() {
+        let input = ["LXR", "", "AAA = (AAA, AAA)"].join("\n");
+
+        let err = parse_day8(&input).unwrap_err();
+        assert_eq!((err.line, err.column), (1, 2));
+        assert!(err.expected.contains("\"L\""));
+        assert!(err.expected.contains("\"R\""));
     }
 }
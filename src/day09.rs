@@ -1,8 +1,10 @@
-use crate::day08::Direction::{Left, Right};
-use aoc_runner_derive::{aoc, aoc_generator};
-use itertools::FoldWhile::{Continue, Done};
-use itertools::{max, Itertools};
-use std::collections::HashMap;
+use crate::error::ParseError;
+use crate::par;
+use crate::stream::{for_each_line, StreamError};
+use num_bigint::{BigInt, TryFromBigIntError};
+use num_rational::BigRational;
+use num_traits::{One, Zero};
+use std::io::BufRead;
 use std::ops::Deref;
 
 #[derive(Eq, PartialEq, Debug)]
@@ -16,34 +18,92 @@ impl Deref for History {
     }
 }
 
+impl From<Vec<isize>> for History {
+    fn from(series: Vec<isize>) -> Self {
+        History(series)
+    }
+}
+
 impl History {
-    fn predict_next(&self) -> isize {
-        if self.all_zeroes() {
-            0
-        } else {
-            self.last().unwrap() + self.new_history().predict_next()
-        }
+    fn predict_next(&self) -> BigInt {
+        self.extrapolate(self.len() as i64)
+    }
+
+    fn predict_previous(&self) -> BigInt {
+        self.extrapolate(-1)
     }
 
-    fn predict_previous(&self) -> isize {
-        if self.all_zeroes() {
-            0
-        } else {
-            self.first().unwrap() - self.new_history().predict_previous()
+    /// extrapolate returns the value of the fitted polynomial at index `k`, where the history
+    /// itself sits at indices `0..len`.
+    pub fn extrapolate(&self, k: i64) -> BigInt {
+        let k = BigInt::from(k);
+
+        // Newton's forward formula: f(k) = sum of Δʲf(0) * C(k, j), with C(k, j) built up
+        // incrementally. Each division is exact, even for negative k.
+        let mut binomial = BigInt::one();
+        let mut value = BigInt::zero();
+        for (j, d) in self.differences().iter().enumerate() {
+            if j > 0 {
+                binomial = binomial * (&k - (j - 1)) / j;
+            }
+
+            value += d * &binomial;
         }
+
+        value
     }
 
-    fn new_history(&self) -> History {
-        History(
-            self.iter()
-                .zip(self.iter().skip(1))
-                .map(|(f, s)| s - f)
-                .collect::<Vec<_>>(),
-        )
+    /// degree returns the degree of the fitted polynomial, or None if it is zero everywhere.
+    pub fn degree(&self) -> Option<usize> {
+        self.differences().iter().rposition(|d| !d.is_zero())
+    }
+
+    /// coefficients returns the fitted polynomial's coefficients, constant term first.
+    pub fn coefficients(&self) -> Vec<BigRational> {
+        let differences = self.differences();
+        let Some(degree) = differences.iter().rposition(|d| !d.is_zero()) else {
+            return vec![];
+        };
+
+        let mut coefficients = vec![BigRational::zero(); degree + 1];
+
+        // falling holds k(k - 1)...(k - j + 1) as powers of k.
+        let mut falling = vec![BigRational::one()];
+        let mut factorial = BigInt::one();
+        for (j, d) in differences.iter().take(degree + 1).enumerate() {
+            if j > 0 {
+                let root = BigRational::from_integer(BigInt::from(j - 1));
+
+                let mut next = vec![BigRational::zero(); falling.len() + 1];
+                for (i, c) in falling.iter().enumerate() {
+                    next[i + 1] += c;
+                    next[i] -= c * &root;
+                }
+
+                falling = next;
+                factorial *= j;
+            }
+
+            let scale = BigRational::new(d.clone(), factorial.clone());
+            for (i, c) in falling.iter().enumerate() {
+                coefficients[i] += c * &scale;
+            }
+        }
+
+        coefficients
     }
 
-    fn all_zeroes(&self) -> bool {
-        self.iter().all(|n| n == &0)
+    /// differences returns the first entry of every row of the difference table.
+    fn differences(&self) -> Vec<BigInt> {
+        let mut row = self.iter().map(|&n| BigInt::from(n)).collect::<Vec<_>>();
+        let mut differences = vec![];
+
+        while let Some(first) = row.first() {
+            differences.push(first.clone());
+            row = row.windows(2).map(|w| &w[1] - &w[0]).collect();
+        }
+
+        differences
     }
 }
 
@@ -55,19 +115,45 @@ peg::parser! {
   }
 }
 
-#[aoc_generator(day9)]
-fn parse_day9(input: &str) -> Vec<History> {
-    input_parser::report(input).unwrap()
+pub fn parse_day9(input: &str) -> Result<Vec<History>, ParseError> {
+    input_parser::report(input).map_err(|e| ParseError::from_peg(9, input, e))
+}
+
+pub fn day9_part1(report: &[History]) -> Result<isize, TryFromBigIntError<BigInt>> {
+    par::map(report, History::predict_next)
+        .into_iter()
+        .sum::<BigInt>()
+        .try_into()
+}
+
+pub fn day9_part2(report: &[History]) -> Result<isize, TryFromBigIntError<BigInt>> {
+    par::map(report, History::predict_previous)
+        .into_iter()
+        .sum::<BigInt>()
+        .try_into()
+}
+
+/// day9_part1_stream is day9_part1 reading one history at a time.
+pub fn day9_part1_stream(input: impl BufRead) -> Result<isize, StreamError> {
+    sum_stream(input, History::predict_next)
 }
 
-#[aoc(day9, part1)]
-fn day9_part1(report: &[History]) -> isize {
-    report.iter().map(|h| h.predict_next()).sum()
+/// day9_part2_stream is day9_part2 reading one history at a time.
+pub fn day9_part2_stream(input: impl BufRead) -> Result<isize, StreamError> {
+    sum_stream(input, History::predict_previous)
 }
 
-#[aoc(day9, part2)]
-fn day9_part2(report: &[History]) -> isize {
-    report.iter().map(|h| h.predict_previous()).sum()
+fn sum_stream(input: impl BufRead, predict: fn(&History) -> BigInt) -> Result<isize, StreamError> {
+    let mut sum = BigInt::zero();
+    for_each_line(input, |line, l| {
+        let history =
+            input_parser::history(l).map_err(|e| ParseError::from_peg(9, l, e).on_line(line))?;
+        sum += predict(&history);
+        Ok(())
+    })?;
+
+    sum.try_into()
+        .map_err(|e: TryFromBigIntError<BigInt>| StreamError::Solve(Box::new(e)))
 }
 
 #[cfg(test)]
@@ -78,7 +164,7 @@ mod tests {
     fn test_parse() {
         let input = ["0 -3 6 9 12 15", "1 3 6 10 15 21", "10 13 16 21 30 45"].join("\n");
         assert_eq!(
-            parse_day9(&input),
+            parse_day9(&input).unwrap(),
             vec![
                 History(vec![0, -3, 6, 9, 12, 15]),
                 History(vec![1, 3, 6, 10, 15, 21]),
@@ -91,18 +177,69 @@ mod tests {
     fn test_predict_next() {
         let h = History(vec![0, 3, 6, 9, 12, 15]);
 
-        assert_eq!(h.predict_next(), 18)
+        assert_eq!(h.predict_next(), BigInt::from(18))
+    }
+
+    #[test]
+    fn test_extrapolate() {
+        let h = History(vec![10, 13, 16, 21, 30, 45]);
+
+        assert_eq!(h.extrapolate(6), BigInt::from(68));
+        assert_eq!(h.extrapolate(-1), BigInt::from(5));
+        assert_eq!(h.extrapolate(2), BigInt::from(16));
+        assert_eq!(h.extrapolate(-3), BigInt::from(-19));
+        assert_eq!(
+            h.extrapolate(1_000_000_000_000),
+            "333333333332333333333337000000000010"
+                .parse::<BigInt>()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_polynomial() {
+        let r = |n, d| BigRational::new(BigInt::from(n), BigInt::from(d));
+
+        let h = History(vec![1, 3, 6, 10, 15, 21]);
+        assert_eq!(h.degree(), Some(2));
+        assert_eq!(h.coefficients(), vec![r(1, 1), r(3, 2), r(1, 2)]);
+
+        let h = History(vec![10, 13, 16, 21, 30, 45]);
+        assert_eq!(h.degree(), Some(3));
+        assert_eq!(
+            h.coefficients(),
+            vec![r(10, 1), r(11, 3), r(-1, 1), r(1, 3)]
+        );
+
+        let h = History(vec![7, 7, 7]);
+        assert_eq!(h.degree(), Some(0));
+        assert_eq!(h.coefficients(), vec![r(7, 1)]);
+
+        let h = History(vec![0, 0]);
+        assert_eq!(h.degree(), None);
+        assert_eq!(h.coefficients(), vec![]);
     }
 
     #[test]
     fn test_part1() {
         let input = ["0 3 6 9 12 15", "1 3 6 10 15 21", "10 13 16 21 30 45"].join("\n");
-        assert_eq!(day9_part1(&parse_day9(&input)), 114);
+        assert_eq!(day9_part1(&parse_day9(&input).unwrap()), Ok(114));
+        assert_eq!(day9_part1_stream(input.as_bytes()).unwrap(), 114);
     }
 
     #[test]
     fn test_part2() {
         let input = ["0 3 6 9 12 15", "1 3 6 10 15 21", "10 13 16 21 30 45"].join("\n");
-        assert_eq!(day9_part2(&parse_day9(&input)), 2);
+        assert_eq!(day9_part2(&parse_day9(&input).unwrap()), Ok(2));
+        assert_eq!(day9_part2_stream(input.as_bytes()).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_prediction_overflows() {
+        let report = parse_day9(&format!("0 {}", isize::MAX)).unwrap();
+
+        assert!(day9_part1(&report).is_err());
+        assert_eq!(day9_part2(&report), Ok(-isize::MAX));
+        assert!(day9_part1_stream(format!("0 {}", isize::MAX).as_bytes()).is_err());
     }
 }
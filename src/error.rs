@@ -0,0 +1,146 @@
+use peg::str::LineCol;
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fmt;
+
+/// ParseError describes where and why a generator rejected its input.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ParseError {
+    pub day: u8,
+    pub line: usize,
+    pub column: usize,
+    pub expected: BTreeSet<String>,
+    pub snippet: String,
+}
+
+impl ParseError {
+    /// new builds an error pointing at the 0-indexed byte `offset` into `input`.
+    pub fn new<I, S>(day: u8, input: &str, offset: usize, expected: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut offset = offset.min(input.len());
+        while !input.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line_start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = input[offset..]
+            .find('\n')
+            .map_or(input.len(), |i| offset + i);
+
+        Self {
+            day,
+            line: input[..line_start].matches('\n').count() + 1,
+            column: input[line_start..offset].chars().count() + 1,
+            expected: expected.into_iter().map(Into::into).collect(),
+            snippet: input[line_start..line_end].to_string(),
+        }
+    }
+
+    pub fn from_peg(day: u8, input: &str, err: peg::error::ParseError<LineCol>) -> Self {
+        Self::new(day, input, err.location.offset, err.expected.tokens())
+    }
+
+    /// on_line renumbers an error found in a single line that sits at `line` of the whole input.
+    pub fn on_line(self, line: usize) -> Self {
+        Self { line, ..self }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "day {}: parse error at line {}, column {}: expected ",
+            self.day, self.line, self.column
+        )?;
+
+        match self.expected.len() {
+            0 => write!(f, "<unreported>")?,
+            1 => write!(f, "{}", self.expected.iter().next().unwrap())?,
+            _ => write!(
+                f,
+                "one of {}",
+                self.expected.iter().cloned().collect::<Vec<_>>().join(", ")
+            )?,
+        }
+
+        let gutter = " ".repeat(self.line.to_string().len());
+        writeln!(f)?;
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", self.line, self.snippet)?;
+        write!(f, "{} | {}^", gutter, " ".repeat(self.column - 1))
+    }
+}
+
+impl Error for ParseError {}
+
+/// Overflow reports an answer too large to hold in a usize.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct Overflow;
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the answer overflows {} bits", usize::BITS)
+    }
+}
+
+impl Error for Overflow {}
+
+/// checked_sum adds up `values`, failing instead of wrapping around.
+pub fn checked_sum(values: impl IntoIterator<Item = usize>) -> Result<usize, Overflow> {
+    values
+        .into_iter()
+        .try_fold(0_usize, |acc, v| acc.checked_add(v).ok_or(Overflow))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_locates_line_and_column() {
+        let input = "first\nsecond line\nthird";
+        let err = ParseError::new(1, input, 13, ["digit"]);
+
+        assert_eq!(err.line, 2);
+        assert_eq!(err.column, 8);
+        assert_eq!(err.snippet, "second line");
+    }
+
+    #[test]
+    fn test_offset_at_end_of_input() {
+        let input = "abc\n";
+        let err = ParseError::new(1, input, 4, ["EOF"]);
+
+        assert_eq!((err.line, err.column), (2, 1));
+        assert_eq!(err.snippet, "");
+    }
+
+    #[test]
+    fn test_offset_inside_a_character() {
+        let input = "1.\u{4be}.2";
+        let err = ParseError::new(3, input, 3, ["number"]);
+
+        assert_eq!((err.line, err.column), (1, 3));
+        assert_eq!(err.snippet, input);
+    }
+
+    #[test]
+    fn test_display() {
+        let input = "Card 1: 1 2 | 3\nCard 2: 4 x | 6";
+        let err = ParseError::new(4, input, 26, ["\"|\"", "['0' ..= '9']"]);
+
+        assert_eq!(
+            err.to_string(),
+            [
+                "day 4: parse error at line 2, column 11: expected one of \"|\", ['0' ..= '9']",
+                "  |",
+                "2 | Card 2: 4 x | 6",
+                "  |           ^",
+            ]
+            .join("\n")
+        );
+    }
+}
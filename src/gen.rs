@@ -0,0 +1,464 @@
+use rand::rngs::StdRng;
+use rand::seq::{IndexedRandom, SliceRandom};
+use rand::{RngExt, SeedableRng};
+use std::collections::HashSet;
+use std::iter;
+
+/// Generator produces random puzzle inputs in the exact format every day's parser accepts. The
+/// same seed and size always produce the same inputs.
+pub struct Generator {
+    rng: StdRng,
+    size: usize,
+}
+
+impl Generator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            size: 10,
+        }
+    }
+
+    /// with_size sets how big the inputs are; each day documents what it counts.
+    pub fn with_size(self, size: usize) -> Self {
+        Self {
+            size: size.max(1),
+            ..self
+        }
+    }
+
+    pub fn input(&mut self, day: u8) -> Option<String> {
+        match day {
+            1 => Some(self.day1()),
+            2 => Some(self.day2()),
+            3 => Some(self.day3()),
+            4 => Some(self.day4()),
+            5 => Some(self.day5()),
+            6 => Some(self.day6()),
+            7 => Some(self.day7()),
+            8 => Some(self.day8()),
+            9 => Some(self.day9()),
+            _ => None,
+        }
+    }
+
+    /// day1 writes `size` lines of letters, digits and spelled-out digits, each with at least
+    /// one digit.
+    pub fn day1(&mut self) -> String {
+        const WORDS: [&str; 9] = [
+            "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+        ];
+
+        self.lines(|rng| {
+            let mut tokens = (0..rng.random_range(0..8))
+                .map(|_| match rng.random_range(0..3) {
+                    0 => WORDS.choose(rng).unwrap().to_string(),
+                    1 => rng.random_range(1..=9).to_string(),
+                    _ => char::from(rng.random_range(b'a'..=b'z')).to_string(),
+                })
+                .collect::<Vec<_>>();
+
+            let at = rng.random_range(0..=tokens.len());
+            tokens.insert(at, rng.random_range(1..=9).to_string());
+
+            tokens.concat()
+        })
+    }
+
+    /// day2 writes `size` games of up to six sets of cubes.
+    pub fn day2(&mut self) -> String {
+        let mut id = 0;
+
+        self.lines(|rng| {
+            let sets = (0..rng.random_range(1..=6))
+                .map(|_| {
+                    let mut colours = ["red", "green", "blue"];
+                    colours.shuffle(rng);
+
+                    colours[..rng.random_range(1..=3)]
+                        .iter()
+                        .map(|c| format!("{} {}", rng.random_range(1..=20), c))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .collect::<Vec<_>>();
+
+            id += 1;
+            format!("Game {}: {}", id, sets.join("; "))
+        })
+    }
+
+    /// day3 writes a `size` by `size` schematic of numbers and symbols.
+    pub fn day3(&mut self) -> String {
+        const SYMBOLS: &[u8] = b"*#+$/@=%-&";
+        let size = self.size;
+
+        self.lines(|rng| {
+            let mut row = String::with_capacity(size);
+
+            while row.len() < size {
+                let left = size - row.len();
+
+                match rng.random_range(0..20) {
+                    0..=2 => {
+                        let digits = rng.random_range(1..=3.min(left));
+                        row.push_str(
+                            &rng.random_range(
+                                10_usize.pow(digits as u32 - 1)..10_usize.pow(digits as u32),
+                            )
+                            .to_string(),
+                        );
+
+                        if row.len() < size {
+                            row.push('.');
+                        }
+                    }
+                    3 => row.push(char::from(*SYMBOLS.choose(rng).unwrap())),
+                    _ => row.push('.'),
+                }
+            }
+
+            row
+        })
+    }
+
+    /// day4 writes `size` cards. Fewer than one number matches on average, so the copies won
+    /// in part 2 stay small, and no card wins cards past the end.
+    pub fn day4(&mut self) -> String {
+        let size = self.size;
+        let mut id = 0;
+
+        self.lines(|rng| {
+            let mut numbers = (1..=99).collect::<Vec<usize>>();
+            numbers.shuffle(rng);
+
+            let (winning, rest) = numbers.split_at(5);
+            let matches = (*[0, 0, 1, 2].choose(rng).unwrap()).min(size - id - 1);
+            let mut have = winning[..matches]
+                .iter()
+                .chain(&rest[..8 - matches])
+                .copied()
+                .collect::<Vec<_>>();
+            have.shuffle(rng);
+
+            let column = |ns: &[usize]| {
+                ns.iter()
+                    .map(|n| format!("{:>2}", n))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            };
+
+            id += 1;
+            format!("Card {:>3}: {} | {}", id, column(winning), column(&have))
+        })
+    }
+
+    /// day5 writes `size` seed ranges and maps of about `size` ranges each, every map moving
+    /// pieces of [0, 2^32) around and leaving some of them in place.
+    pub fn day5(&mut self) -> String {
+        const LIMIT: usize = 1 << 32;
+        const NAMES: [&str; 8] = [
+            "seed",
+            "soil",
+            "fertilizer",
+            "water",
+            "light",
+            "temperature",
+            "humidity",
+            "location",
+        ];
+
+        let rng = &mut self.rng;
+        let seeds = (0..self.size)
+            .map(|_| {
+                let start = rng.random_range(0..LIMIT / 2);
+                format!("{} {}", start, rng.random_range(1..LIMIT / 64))
+            })
+            .collect::<Vec<_>>();
+
+        let mut text = format!("seeds: {}", seeds.join(" "));
+
+        for names in NAMES.windows(2) {
+            let mut cuts = (1..self.size)
+                .map(|_| rng.random_range(0..LIMIT))
+                .chain([0, LIMIT])
+                .collect::<Vec<_>>();
+            cuts.sort();
+            cuts.dedup();
+
+            let mut pieces = cuts
+                .windows(2)
+                .map(|w| (w[0], w[1] - w[0]))
+                .collect::<Vec<_>>();
+            pieces.shuffle(rng);
+
+            // Laying the shuffled pieces out one after another makes every map a permutation.
+            let mut destination = 0;
+            let mut ranges = vec![];
+            for (source, length) in pieces {
+                if rng.random_bool(0.8) {
+                    ranges.push(format!("{} {} {}", destination, source, length));
+                }
+                destination += length;
+            }
+            if ranges.is_empty() {
+                ranges.push(format!("0 0 {}", LIMIT));
+            }
+            ranges.shuffle(rng);
+
+            text.push_str(&format!(
+                "\n\n{}-to-{} map:\n{}",
+                names[0],
+                names[1],
+                ranges.join("\n")
+            ));
+        }
+
+        text
+    }
+
+    /// day6 writes `size` races, at most nine, keeping every time short enough for part 2 to
+    /// read the concatenated race as a number.
+    pub fn day6(&mut self) -> String {
+        let races = self.size.min(9);
+        let digits = 9 / races;
+        let rng = &mut self.rng;
+
+        let (times, distances): (Vec<_>, Vec<_>) = (0..races)
+            .map(|_| {
+                let time = rng.random_range(1..10_usize.pow(digits as u32));
+                let distance = rng.random_range(0..=time * time / 4);
+                (time, distance)
+            })
+            .unzip();
+
+        let width = 2 * digits + 1;
+        let row = |ns: &[usize]| {
+            ns.iter()
+                .map(|n| format!("{:>width$}", n))
+                .collect::<String>()
+        };
+
+        format!("Time:    {}\nDistance:{}", row(&times), row(&distances))
+    }
+
+    /// day7 writes `size` hands with their bids.
+    pub fn day7(&mut self) -> String {
+        const CARDS: &[u8] = b"23456789TJQKA";
+
+        self.lines(|rng| {
+            let hand = (0..5)
+                .map(|_| char::from(*CARDS.choose(rng).unwrap()))
+                .collect::<String>();
+
+            format!("{} {}", hand, rng.random_range(1..=1000))
+        })
+    }
+
+    /// day8 writes up to `size` directions and a network where AAA leads to ZZZ, and every
+    /// other start loops through its end at a multiple of the directions' length, so both parts
+    /// have an answer.
+    pub fn day8(&mut self) -> String {
+        const CHARACTERS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+        let directions = self.size.min(300);
+        let ghosts = (self.size / 4).clamp(1, 6);
+        let rng = &mut self.rng;
+
+        let path = (0..directions)
+            .map(|_| *[b'L', b'R'].choose(rng).unwrap())
+            .collect::<Vec<_>>();
+
+        let mut taken = HashSet::from(["AAA".to_string(), "ZZZ".to_string()]);
+        let mut name = |rng: &mut StdRng, last: Option<u8>| loop {
+            let mut n = (0..3)
+                .map(|_| *CHARACTERS.choose(rng).unwrap())
+                .collect::<Vec<_>>();
+
+            match last {
+                Some(c) => n[2] = c,
+                None if n[2] == b'A' || n[2] == b'Z' => continue,
+                None => {}
+            }
+
+            let n = String::from_utf8(n).unwrap();
+            if taken.insert(n.clone()) {
+                return n;
+            }
+        };
+
+        // Every ghost runs from its start into a loop of nodes that ends at its end node.
+        let mut loops = vec![];
+        for g in 0..ghosts {
+            let (start, end) = match g {
+                0 => ("AAA".to_string(), "ZZZ".to_string()),
+                _ => (name(rng, Some(b'A')), name(rng, Some(b'Z'))),
+            };
+
+            let length = directions * [1, 2, 3, 5, 7].choose(rng).unwrap();
+            let nodes = (1..length)
+                .map(|_| name(rng, None))
+                .chain(iter::once(end))
+                .collect::<Vec<_>>();
+
+            loops.push((start, nodes));
+        }
+
+        let all = loops
+            .iter()
+            .flat_map(|(start, nodes)| iter::once(start).chain(nodes))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut instructions = vec![];
+        for (start, nodes) in loops.iter() {
+            // The node reached after step t is left by direction t + 1.
+            let froms = iter::once(start).chain(nodes.iter());
+            for (t, from) in froms.enumerate() {
+                let next = &nodes[t % nodes.len()];
+                let decoy = all.choose(rng).unwrap();
+
+                let (left, right) = match path[t % directions] {
+                    b'L' => (next, decoy),
+                    _ => (decoy, next),
+                };
+                instructions.push(format!("{} = ({}, {})", from, left, right));
+            }
+        }
+        instructions.shuffle(rng);
+
+        format!(
+            "{}\n\n{}",
+            String::from_utf8(path).unwrap(),
+            instructions.join("\n")
+        )
+    }
+
+    /// day9 writes `size` histories, each a polynomial of degree up to five sampled at up to 21
+    /// points.
+    pub fn day9(&mut self) -> String {
+        self.lines(|rng| {
+            let degree = rng.random_range(0..=5);
+            let mut differences = (0..=degree)
+                .map(|_| isize::from(rng.random_range(-10..=10_i16)))
+                .collect::<Vec<_>>();
+
+            (0..rng.random_range(degree + 2..=21))
+                .map(|_| {
+                    let value = differences[0];
+                    for i in 0..degree {
+                        differences[i] += differences[i + 1];
+                    }
+                    value.to_string()
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+    }
+
+    fn lines(&mut self, mut line: impl FnMut(&mut StdRng) -> String) -> String {
+        (0..self.size)
+            .map(|_| line(&mut self.rng))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solutions::solutions;
+    use crate::{day02, day03, day04, day05, day06, day07, day08, day09};
+    use proptest::prelude::*;
+
+    fn generate(day: u8, seed: u64, size: usize) -> String {
+        Generator::new(seed).with_size(size).input(day).unwrap()
+    }
+
+    #[test]
+    fn test_same_seed_same_input() {
+        for day in 1..=9 {
+            assert_eq!(generate(day, 7, 20), generate(day, 7, 20));
+            assert_ne!(generate(day, 7, 20), generate(day, 8, 20));
+        }
+        assert_eq!(Generator::new(0).input(10), None);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_every_input_solves(seed in any::<u64>(), size in 1..40_usize) {
+            for solution in solutions() {
+                let input = generate(solution.day, seed, size);
+
+                prop_assert!(solution.run(&input).is_ok(), "day {} part {}:\n{}", solution.day, solution.part, input);
+            }
+        }
+
+        #[test]
+        fn prop_day1(seed in any::<u64>(), size in 1..40_usize) {
+            let input = generate(1, seed, size);
+
+            prop_assert_eq!(input.lines().count(), size);
+            prop_assert!(input.lines().all(|l| l.chars().any(|c| c.is_ascii_digit())));
+        }
+
+        #[test]
+        fn prop_day2_parses(seed in any::<u64>(), size in 1..40_usize) {
+            let games = day02::parse_day2(&generate(2, seed, size)).unwrap();
+
+            prop_assert_eq!(games.games().len(), size);
+        }
+
+        #[test]
+        fn prop_day3_parses(seed in any::<u64>(), size in 1..40_usize) {
+            let input = generate(3, seed, size);
+            day03::parse_day3(&input).unwrap();
+
+            prop_assert_eq!(input.lines().count(), size);
+            prop_assert!(input.lines().all(|l| l.len() == size));
+        }
+
+        #[test]
+        fn prop_day4_parses(seed in any::<u64>(), size in 1..40_usize) {
+            let cards = day04::parse_day4(&generate(4, seed, size)).unwrap();
+
+            prop_assert_eq!(cards.len(), size);
+        }
+
+        #[test]
+        fn prop_day5_parses(seed in any::<u64>(), size in 1..40_usize) {
+            day05::parse_day5(&generate(5, seed, size)).unwrap();
+        }
+
+        #[test]
+        fn prop_day6_parses(seed in any::<u64>(), size in 1..40_usize) {
+            let input = generate(6, seed, size);
+
+            prop_assert_eq!(day06::parse_day6(&input).unwrap().len(), size.min(9));
+            day06::parse_day6_part2(&input).unwrap();
+        }
+
+        #[test]
+        fn prop_day7_parses(seed in any::<u64>(), size in 1..40_usize) {
+            let hands = day07::parse_day7(&generate(7, seed, size)).unwrap();
+
+            prop_assert_eq!(hands.len(), size);
+        }
+
+        #[test]
+        fn prop_day8_parses(seed in any::<u64>(), size in 1..40_usize) {
+            let map = day08::parse_day8(&generate(8, seed, size)).unwrap();
+
+            prop_assert_eq!(day08::day8_part1(&map).unwrap() % size, 0);
+            prop_assert_eq!(day08::day8_part2(&map).unwrap() % size, 0.into());
+        }
+
+        #[test]
+        fn prop_day9_parses(seed in any::<u64>(), size in 1..40_usize) {
+            let report = day09::parse_day9(&generate(9, seed, size)).unwrap();
+
+            prop_assert_eq!(report.len(), size);
+            prop_assert!(report.iter().all(|h| h.degree().is_none_or(|d| d <= 5)));
+        }
+    }
+}
@@ -0,0 +1,449 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone, Ord, PartialOrd)]
+pub struct Point {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Point {
+    pub fn new(x: usize, y: usize) -> Self {
+        Self { x, y }
+    }
+
+    /// offset returns the point moved by (dx, dy), or None if it would leave the first quadrant.
+    pub fn offset(&self, dx: isize, dy: isize) -> Option<Point> {
+        Some(Point {
+            x: self.x.checked_add_signed(dx)?,
+            y: self.y.checked_add_signed(dy)?,
+        })
+    }
+}
+
+/// Span is an inclusive horizontal run of cells on a single row.
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone, Ord, PartialOrd)]
+pub struct Span {
+    pub from: Point,
+    pub to: Point,
+}
+
+impl Span {
+    pub fn points(&self) -> impl Iterator<Item = Point> + '_ {
+        (self.from.x..=self.to.x).map(|x| Point { x, y: self.from.y })
+    }
+
+    pub fn has_adjacent_point(&self, p: &Point) -> bool {
+        let (xf, xt, y) = (
+            self.from.x as isize,
+            self.to.x as isize,
+            self.from.y as isize,
+        );
+
+        if !(y - 1..=y + 1).contains(&(p.y as isize)) {
+            return false;
+        }
+
+        if !(xf - 1..=xt + 1).contains(&(p.x as isize)) {
+            return false;
+        }
+
+        true
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum GridError {
+    /// A row had a different width from the first one.
+    Ragged {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GridError::Ragged {
+                row,
+                expected,
+                found,
+            } => write!(f, "row {} has {} cells, expected {}", row, found, expected),
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+const NEIGHBOURS_4: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+const NEIGHBOURS_8: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+];
+
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Self, GridError> {
+        let width = rows.first().map_or(0, Vec::len);
+        let height = rows.len();
+
+        let mut cells = Vec::with_capacity(width * height);
+        for (row, r) in rows.into_iter().enumerate() {
+            if r.len() != width {
+                return Err(GridError::Ragged {
+                    row,
+                    expected: width,
+                    found: r.len(),
+                });
+            }
+
+            cells.extend(r);
+        }
+
+        Ok(Self {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    /// parse_with builds a grid from the lines of `input`, mapping every char through `f`.
+    pub fn parse_with(input: &str, mut f: impl FnMut(char) -> T) -> Result<Self, GridError> {
+        Self::from_rows(
+            input
+                .lines()
+                .map(|l| l.chars().map(&mut f).collect())
+                .collect(),
+        )
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, p: &Point) -> bool {
+        p.x < self.width && p.y < self.height
+    }
+
+    pub fn get(&self, p: &Point) -> Option<&T> {
+        self.contains(p)
+            .then(|| &self.cells[p.y * self.width + p.x])
+    }
+
+    pub fn get_mut(&mut self, p: &Point) -> Option<&mut T> {
+        if self.contains(p) {
+            Some(&mut self.cells[p.y * self.width + p.x])
+        } else {
+            None
+        }
+    }
+
+    pub fn row(&self, y: usize) -> Option<&[T]> {
+        (y < self.height).then(|| &self.cells[y * self.width..(y + 1) * self.width])
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(|y| &self.cells[y * self.width..(y + 1) * self.width])
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        let x = if x < self.width { x } else { self.cells.len() };
+
+        self.cells.iter().skip(x).step_by(self.width.max(1))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    /// points iterates every point of the grid in row-major order.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| Point { x, y }))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    pub fn neighbours4(&self, p: &Point) -> impl Iterator<Item = Point> + '_ {
+        self.neighbours(*p, &NEIGHBOURS_4)
+    }
+
+    pub fn neighbours8(&self, p: &Point) -> impl Iterator<Item = Point> + '_ {
+        self.neighbours(*p, &NEIGHBOURS_8)
+    }
+
+    fn neighbours(
+        &self,
+        p: Point,
+        deltas: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = Point> + '_ {
+        deltas
+            .iter()
+            .filter_map(move |&(dx, dy)| p.offset(dx, dy))
+            .filter(|n| self.contains(n))
+    }
+
+    /// run_at returns the longest horizontal run starting at `p` whose cells all match `pred`.
+    pub fn run_at(&self, p: &Point, pred: impl Fn(&T) -> bool) -> Option<Span> {
+        let row = self.row(p.y)?;
+        let len = row.get(p.x..)?.iter().take_while(|c| pred(c)).count();
+
+        (len > 0).then(|| Span {
+            from: *p,
+            to: Point {
+                x: p.x + len - 1,
+                y: p.y,
+            },
+        })
+    }
+
+    /// runs iterates every maximal horizontal run of cells matching `pred`, in row-major order.
+    pub fn runs<'a>(&'a self, pred: impl Fn(&T) -> bool + 'a) -> impl Iterator<Item = Span> + 'a {
+        (0..self.height).flat_map(move |y| {
+            let row = self.row(y).unwrap();
+            let mut spans = vec![];
+
+            let mut x = 0;
+            while x < self.width {
+                if pred(&row[x]) {
+                    let span = self.run_at(&Point { x, y }, &pred).unwrap();
+
+                    x = span.to.x + 1;
+                    spans.push(span);
+                } else {
+                    x += 1;
+                }
+            }
+
+            spans
+        })
+    }
+
+    pub fn span(&self, span: &Span) -> &[T] {
+        &self.row(span.from.y).unwrap()[span.from.x..=span.to.x]
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn transpose(&self) -> Self {
+        Self {
+            width: self.height,
+            height: self.width,
+            cells: self.columns().flatten().cloned().collect(),
+        }
+    }
+
+    /// rotate_cw rotates the grid a quarter turn clockwise.
+    pub fn rotate_cw(&self) -> Self {
+        Self {
+            width: self.height,
+            height: self.width,
+            cells: self
+                .columns()
+                .flat_map(|c| c.cloned().collect::<Vec<_>>().into_iter().rev())
+                .collect(),
+        }
+    }
+
+    /// rotate_ccw rotates the grid a quarter turn counter-clockwise.
+    pub fn rotate_ccw(&self) -> Self {
+        Self {
+            width: self.height,
+            height: self.width,
+            cells: (0..self.width)
+                .rev()
+                .flat_map(|x| self.column(x).cloned())
+                .collect(),
+        }
+    }
+}
+
+impl Grid<char> {
+    pub fn parse(input: &str) -> Result<Self, GridError> {
+        Self::parse_with(input, |c| c)
+    }
+}
+
+impl FromStr for Grid<char> {
+    type Err = GridError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Grid<char> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+
+            write!(f, "{}", row.iter().collect::<String>())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Point) -> &Self::Output {
+        self.get(&p).expect("point out of bounds")
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut Self::Output {
+        self.get_mut(&p).expect("point out of bounds")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid() -> Grid<char> {
+        ["abc", "def"].join("\n").parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let g = grid();
+
+        assert_eq!((g.width(), g.height()), (3, 2));
+        assert_eq!(g[Point::new(2, 1)], 'f');
+        assert_eq!(g.get(&Point::new(3, 0)), None);
+        assert_eq!(g.get(&Point::new(0, 2)), None);
+    }
+
+    #[test]
+    fn test_parse_ragged() {
+        assert_eq!(
+            Grid::parse("abc\nde\nfgh"),
+            Err(GridError::Ragged {
+                row: 1,
+                expected: 3,
+                found: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let g = grid();
+
+        assert_eq!(
+            g.rows().collect::<Vec<_>>(),
+            vec![&['a', 'b', 'c'], &['d', 'e', 'f']]
+        );
+        assert_eq!(
+            g.columns()
+                .map(|c| c.collect::<String>())
+                .collect::<Vec<_>>(),
+            vec!["ad", "be", "cf"]
+        );
+        assert_eq!(g.column(3).count(), 0);
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let g = grid();
+
+        assert_eq!(
+            g.neighbours4(&Point::new(0, 0)).collect::<Vec<_>>(),
+            vec![Point::new(1, 0), Point::new(0, 1)]
+        );
+        assert_eq!(g.neighbours8(&Point::new(1, 0)).count(), 5);
+        assert_eq!(g.neighbours8(&Point::new(1, 1)).count(), 5);
+    }
+
+    #[test]
+    fn test_runs() {
+        let g: Grid<char> = ["467..114..", "...*......", "..35..6333"]
+            .join("\n")
+            .parse()
+            .unwrap();
+
+        let runs = g.runs(char::is_ascii_digit).collect::<Vec<_>>();
+        assert_eq!(
+            runs.iter()
+                .map(|s| g.span(s).iter().collect::<String>())
+                .collect::<Vec<_>>(),
+            vec!["467", "114", "35", "6333"]
+        );
+        assert_eq!(
+            runs[3],
+            Span {
+                from: Point::new(6, 2),
+                to: Point::new(9, 2)
+            }
+        );
+        assert_eq!(g.run_at(&Point::new(3, 0), char::is_ascii_digit), None);
+    }
+
+    #[test]
+    fn test_span_has_adjacent_point() {
+        let span = Span {
+            from: Point::new(2, 2),
+            to: Point::new(3, 2),
+        };
+
+        assert!(span.has_adjacent_point(&Point::new(1, 1)));
+        assert!(span.has_adjacent_point(&Point::new(4, 3)));
+        assert!(!span.has_adjacent_point(&Point::new(5, 2)));
+        assert!(!span.has_adjacent_point(&Point::new(2, 0)));
+    }
+
+    #[test]
+    fn test_transpose_and_rotate() {
+        let g = grid();
+
+        assert_eq!(g.transpose().to_string(), "ad\nbe\ncf");
+        assert_eq!(g.rotate_cw().to_string(), "da\neb\nfc");
+        assert_eq!(g.rotate_ccw().to_string(), "cf\nbe\nad");
+        assert_eq!(g.rotate_cw().rotate_ccw(), g);
+        assert_eq!(g.transpose().transpose(), g);
+    }
+}
@@ -0,0 +1,373 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use std::{fmt, fs, io, thread};
+
+/// InputProvider hands out the puzzle input of a given day.
+pub trait InputProvider {
+    fn input(&self, year: u16, day: u8) -> Result<String, InputError>;
+}
+
+#[derive(Debug)]
+pub enum InputError {
+    Io(PathBuf, io::Error),
+    /// The file is already cached, so it was not downloaded again.
+    AlreadyCached(PathBuf),
+    Http {
+        url: String,
+        status: u16,
+    },
+    Transport {
+        url: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            InputError::AlreadyCached(path) => {
+                write!(
+                    f,
+                    "{} is already cached, refusing to download it again",
+                    path.display()
+                )
+            }
+            InputError::Http { url, status } => write!(f, "GET {} returned {}", url, status),
+            InputError::Transport { url, message } => write!(f, "GET {} failed: {}", url, message),
+        }
+    }
+}
+
+impl Error for InputError {}
+
+/// LocalDirectory reads inputs laid out as `<root>/<year>/day<N>.txt`.
+#[derive(Debug, Clone)]
+pub struct LocalDirectory {
+    root: PathBuf,
+}
+
+impl LocalDirectory {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn path(&self, year: u16, day: u8) -> PathBuf {
+        self.root
+            .join(year.to_string())
+            .join(format!("day{}.txt", day))
+    }
+
+    pub fn contains(&self, year: u16, day: u8) -> bool {
+        self.path(year, day).is_file()
+    }
+
+    /// partial_path is where an input is written before it is moved into place, next to it so
+    /// the move stays on one filesystem.
+    fn partial_path(&self, year: u16, day: u8) -> PathBuf {
+        self.root
+            .join(year.to_string())
+            .join(format!(".day{}.txt.part", day))
+    }
+
+    /// store writes the input in full before moving it into place, since a cached input is
+    /// never downloaded again and a truncated one would be reused forever.
+    fn store(&self, year: u16, day: u8, input: &str) -> Result<PathBuf, InputError> {
+        let path = self.path(year, day);
+        let partial = self.partial_path(year, day);
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| InputError::Io(dir.to_path_buf(), e))?;
+        }
+        fs::write(&partial, input)
+            .and_then(|_| fs::rename(&partial, &path))
+            .map_err(|e| {
+                let _ = fs::remove_file(&partial);
+                InputError::Io(path.clone(), e)
+            })?;
+
+        Ok(path)
+    }
+}
+
+impl Default for LocalDirectory {
+    fn default() -> Self {
+        Self::new("input")
+    }
+}
+
+impl InputProvider for LocalDirectory {
+    fn input(&self, year: u16, day: u8) -> Result<String, InputError> {
+        let path = self.path(year, day);
+
+        fs::read_to_string(&path).map_err(|e| InputError::Io(path, e))
+    }
+}
+
+/// Http downloads inputs from the Advent of Code website with a session cookie, caching them
+/// in a LocalDirectory. Cached inputs are never downloaded again, and requests are spaced at
+/// least `min_interval` apart to go easy on the server.
+pub struct Http {
+    base_url: String,
+    session: String,
+    cache: LocalDirectory,
+    min_interval: Duration,
+    last_request: Mutex<Option<Instant>>,
+}
+
+impl Http {
+    pub fn new(session: impl Into<String>) -> Self {
+        Self {
+            base_url: "https://adventofcode.com".to_string(),
+            session: session.into(),
+            cache: LocalDirectory::default(),
+            min_interval: Duration::from_secs(5),
+            last_request: Mutex::new(None),
+        }
+    }
+
+    pub fn with_base_url(self, base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+            ..self
+        }
+    }
+
+    pub fn with_cache(self, cache: LocalDirectory) -> Self {
+        Self { cache, ..self }
+    }
+
+    pub fn with_min_interval(self, min_interval: Duration) -> Self {
+        Self {
+            min_interval,
+            ..self
+        }
+    }
+
+    pub fn cache(&self) -> &LocalDirectory {
+        &self.cache
+    }
+
+    /// download fetches the input into the cache, refusing if it is already there.
+    pub fn download(&self, year: u16, day: u8) -> Result<PathBuf, InputError> {
+        if self.cache.contains(year, day) {
+            return Err(InputError::AlreadyCached(self.cache.path(year, day)));
+        }
+
+        let input = self.fetch(year, day)?;
+        self.cache.store(year, day, &input)
+    }
+
+    fn fetch(&self, year: u16, day: u8) -> Result<String, InputError> {
+        let url = format!("{}/{}/day/{}/input", self.base_url, year, day);
+
+        self.throttle();
+
+        let agent: ureq::Agent = ureq::Agent::config_builder()
+            .http_status_as_error(false)
+            .build()
+            .into();
+        let transport = |e: ureq::Error| InputError::Transport {
+            url: url.clone(),
+            message: e.to_string(),
+        };
+
+        let mut response = agent
+            .get(&url)
+            .header("Cookie", &format!("session={}", self.session))
+            .header(
+                "User-Agent",
+                "github.com/mahmoudfarouq/advent2023 input provider",
+            )
+            .call()
+            .map_err(transport)?;
+
+        match response.status().as_u16() {
+            200 => response.body_mut().read_to_string().map_err(transport),
+            status => Err(InputError::Http { url, status }),
+        }
+    }
+
+    /// throttle sleeps until at least min_interval has passed since the previous request.
+    fn throttle(&self) {
+        let mut last_request = self.last_request.lock().unwrap();
+
+        if let Some(last) = *last_request {
+            if let Some(wait) = self.min_interval.checked_sub(last.elapsed()) {
+                thread::sleep(wait);
+            }
+        }
+
+        *last_request = Some(Instant::now());
+    }
+}
+
+impl InputProvider for Http {
+    fn input(&self, year: u16, day: u8) -> Result<String, InputError> {
+        if !self.cache.contains(year, day) {
+            self.download(year, day)?;
+        }
+
+        self.cache.input(year, day)
+    }
+}
+
+/// from_env picks the Http provider when `AOC_SESSION` is set, and plain files otherwise.
+pub fn from_env(root: impl AsRef<Path>) -> Box<dyn InputProvider> {
+    let cache = LocalDirectory::new(root.as_ref());
+
+    match std::env::var("AOC_SESSION") {
+        Ok(session) if !session.is_empty() => Box::new(Http::new(session).with_cache(cache)),
+        _ => Box::new(cache),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    /// stub serves `body` for every request, recording the request heads it receives.
+    fn stub(status: u16, body: &'static str) -> (String, Arc<Mutex<Vec<Vec<String>>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(vec![]));
+
+        let seen = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+
+                let head = BufReader::new(&stream)
+                    .lines()
+                    .map(Result::unwrap)
+                    .take_while(|l| !l.is_empty())
+                    .collect::<Vec<_>>();
+                seen.lock().unwrap().push(head);
+
+                write!(
+                    stream,
+                    "HTTP/1.1 {} Stub\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+        });
+
+        (format!("http://{}", addr), requests)
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("advent2023-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_local_directory() {
+        let dir = temp_dir("local");
+        let local = LocalDirectory::new(&dir);
+        local.store(2023, 4, "Card 1: 1 | 1").unwrap();
+
+        assert_eq!(local.path(2023, 4), dir.join("2023").join("day4.txt"));
+        assert_eq!(local.input(2023, 4).unwrap(), "Card 1: 1 | 1");
+        assert!(matches!(local.input(2023, 5), Err(InputError::Io(..))));
+    }
+
+    #[test]
+    fn test_local_directory_failed_store() {
+        let dir = temp_dir("partial");
+        let local = LocalDirectory::new(&dir);
+        // Nothing can be written over a directory.
+        fs::create_dir_all(local.partial_path(2023, 4)).unwrap();
+
+        assert!(matches!(
+            local.store(2023, 4, "Card 1: 1 | 1"),
+            Err(InputError::Io(..))
+        ));
+        assert!(!local.contains(2023, 4));
+
+        local.store(2023, 5, "seeds: 1 2").unwrap();
+        assert!(local.contains(2023, 5));
+        assert!(!local.partial_path(2023, 5).exists());
+    }
+
+    #[test]
+    fn test_http_downloads_and_caches() {
+        let (url, requests) = stub(200, "seeds: 1 2\n");
+        let dir = temp_dir("http");
+        let http = Http::new("secret")
+            .with_base_url(url)
+            .with_cache(LocalDirectory::new(&dir))
+            .with_min_interval(Duration::ZERO);
+
+        assert_eq!(http.input(2023, 5).unwrap(), "seeds: 1 2\n");
+        assert_eq!(http.input(2023, 5).unwrap(), "seeds: 1 2\n");
+        assert_eq!(
+            fs::read_to_string(dir.join("2023/day5.txt")).unwrap(),
+            "seeds: 1 2\n"
+        );
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0][0], "GET /2023/day/5/input HTTP/1.1");
+        assert!(requests[0]
+            .iter()
+            .any(|h| h.eq_ignore_ascii_case("cookie: session=secret")));
+    }
+
+    #[test]
+    fn test_http_refuses_to_download_cached() {
+        let (url, requests) = stub(200, "new");
+        let dir = temp_dir("cached");
+        let cache = LocalDirectory::new(&dir);
+        cache.store(2023, 1, "old").unwrap();
+
+        let http = Http::new("secret").with_base_url(url).with_cache(cache);
+
+        assert!(matches!(
+            http.download(2023, 1),
+            Err(InputError::AlreadyCached(_))
+        ));
+        assert_eq!(http.input(2023, 1).unwrap(), "old");
+        assert!(requests.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_http_error_status() {
+        let (url, _) = stub(400, "Please log in");
+        let dir = temp_dir("status");
+        let http = Http::new("expired")
+            .with_base_url(url)
+            .with_cache(LocalDirectory::new(&dir));
+
+        assert!(matches!(
+            http.input(2023, 2),
+            Err(InputError::Http { status: 400, .. })
+        ));
+        assert!(!dir.join("2023/day2.txt").exists());
+    }
+
+    #[test]
+    fn test_http_rate_limit() {
+        let (url, _) = stub(200, "x");
+        let dir = temp_dir("throttle");
+        let http = Http::new("secret")
+            .with_base_url(url)
+            .with_cache(LocalDirectory::new(&dir))
+            .with_min_interval(Duration::from_millis(300));
+
+        let start = Instant::now();
+        http.download(2023, 1).unwrap();
+        http.download(2023, 2).unwrap();
+        http.download(2023, 3).unwrap();
+
+        assert!(start.elapsed() >= Duration::from_millis(600));
+    }
+}
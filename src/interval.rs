@@ -0,0 +1,351 @@
+use std::fmt;
+
+/// Interval is the half-open range of integers `start..end`.
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone, Ord, PartialOrd)]
+pub struct Interval {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Interval {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    pub fn len(&self) -> usize {
+        self.end.saturating_sub(self.start)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.end <= self.start
+    }
+
+    pub fn contains(&self, n: usize) -> bool {
+        self.start <= n && n < self.end
+    }
+
+    pub fn intersection(&self, other: &Interval) -> Option<Interval> {
+        let i = Interval {
+            start: self.start.max(other.start),
+            end: self.end.min(other.end),
+        };
+
+        (!i.is_empty()).then_some(i)
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}, {})", self.start, self.end)
+    }
+}
+
+/// IntervalSet is a set of integers stored as sorted, disjoint and non-adjacent intervals.
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Default)]
+pub struct IntervalSet {
+    intervals: Vec<Interval>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// insert adds every integer of `interval`, coalescing with whatever it touches.
+    pub fn insert(&mut self, interval: Interval) {
+        if interval.is_empty() {
+            return;
+        }
+
+        let lo = self.intervals.partition_point(|i| i.end < interval.start);
+        let hi = self.intervals.partition_point(|i| i.start <= interval.end);
+
+        let merged = self.intervals[lo..hi]
+            .iter()
+            .fold(interval, |acc, i| Interval {
+                start: acc.start.min(i.start),
+                end: acc.end.max(i.end),
+            });
+
+        self.intervals.splice(lo..hi, [merged]);
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Interval> {
+        self.intervals.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// size returns how many integers the set covers.
+    pub fn size(&self) -> usize {
+        self.intervals.iter().map(Interval::len).sum()
+    }
+
+    pub fn min(&self) -> Option<usize> {
+        self.intervals.first().map(|i| i.start)
+    }
+
+    pub fn max(&self) -> Option<usize> {
+        self.intervals.last().map(|i| i.end - 1)
+    }
+
+    pub fn contains(&self, n: usize) -> bool {
+        let idx = self.intervals.partition_point(|i| i.end <= n);
+
+        self.intervals.get(idx).is_some_and(|i| i.contains(n))
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        self.iter().chain(other.iter()).copied().collect()
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let (mut a, mut b) = (self.iter().peekable(), other.iter().peekable());
+        let mut intervals = vec![];
+
+        while let (Some(x), Some(y)) = (a.peek(), b.peek()) {
+            if let Some(i) = x.intersection(y) {
+                intervals.push(i);
+            }
+
+            if x.end < y.end {
+                a.next();
+            } else {
+                b.next();
+            }
+        }
+
+        // Pieces of two coalesced sets can never touch, so no further merging is needed.
+        IntervalSet { intervals }
+    }
+
+    /// complement returns every integer of `bounds` that is not in the set.
+    pub fn complement(&self, bounds: Interval) -> IntervalSet {
+        let mut intervals = vec![];
+        let mut cursor = bounds.start;
+
+        for i in self.iter() {
+            if i.start >= bounds.end {
+                break;
+            }
+
+            if i.start > cursor {
+                intervals.push(Interval::new(cursor, i.start));
+            }
+
+            cursor = cursor.max(i.end);
+        }
+
+        if cursor < bounds.end {
+            intervals.push(Interval::new(cursor, bounds.end));
+        }
+
+        IntervalSet { intervals }
+    }
+
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        match (self.intervals.first(), self.intervals.last()) {
+            (Some(first), Some(last)) => {
+                let bounds = Interval::new(first.start, last.end);
+
+                self.intersection(&other.complement(bounds))
+            }
+            _ => IntervalSet::new(),
+        }
+    }
+}
+
+impl From<Interval> for IntervalSet {
+    fn from(interval: Interval) -> Self {
+        std::iter::once(interval).collect()
+    }
+}
+
+impl FromIterator<Interval> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Interval>>(iter: I) -> Self {
+        let mut intervals = iter
+            .into_iter()
+            .filter(|i| !i.is_empty())
+            .collect::<Vec<_>>();
+        intervals.sort();
+
+        let mut coalesced: Vec<Interval> = Vec::with_capacity(intervals.len());
+        for i in intervals {
+            match coalesced.last_mut() {
+                Some(last) if i.start <= last.end => last.end = last.end.max(i.end),
+                _ => coalesced.push(i),
+            }
+        }
+
+        IntervalSet {
+            intervals: coalesced,
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a IntervalSet {
+    type Item = &'a Interval;
+    type IntoIter = std::slice::Iter<'a, Interval>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::collections::BTreeSet;
+
+    const UNIVERSE: usize = 64;
+
+    fn intervals() -> impl Strategy<Value = Vec<Interval>> {
+        prop::collection::vec(
+            (0..UNIVERSE, 0..12_usize).prop_map(|(s, l)| Interval::new(s, (s + l).min(UNIVERSE))),
+            0..8,
+        )
+    }
+
+    fn set(intervals: &[Interval]) -> IntervalSet {
+        intervals.iter().copied().collect()
+    }
+
+    fn points(set: &IntervalSet) -> BTreeSet<usize> {
+        set.iter().flat_map(|i| i.start..i.end).collect()
+    }
+
+    fn brute(intervals: &[Interval]) -> BTreeSet<usize> {
+        intervals.iter().flat_map(|i| i.start..i.end).collect()
+    }
+
+    fn assert_normalized(set: &IntervalSet) {
+        for i in set.iter() {
+            assert!(!i.is_empty(), "{:?} has an empty interval", set);
+        }
+
+        for (a, b) in set.iter().zip(set.iter().skip(1)) {
+            assert!(a.end < b.start, "{:?} is not coalesced", set);
+        }
+    }
+
+    #[test]
+    fn test_coalesces_adjacent_and_overlapping() {
+        let set: IntervalSet = [
+            Interval::new(5, 8),
+            Interval::new(0, 2),
+            Interval::new(2, 4),
+            Interval::new(7, 10),
+            Interval::new(12, 12),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            set.iter().copied().collect::<Vec<_>>(),
+            vec![Interval::new(0, 4), Interval::new(5, 10)]
+        );
+        assert_eq!(set.size(), 9);
+        assert_eq!((set.min(), set.max()), (Some(0), Some(9)));
+    }
+
+    #[test]
+    fn test_complement() {
+        let set: IntervalSet = [Interval::new(2, 4), Interval::new(6, 8)]
+            .into_iter()
+            .collect();
+
+        assert_eq!(
+            set.complement(Interval::new(0, 10))
+                .iter()
+                .copied()
+                .collect::<Vec<_>>(),
+            vec![
+                Interval::new(0, 2),
+                Interval::new(4, 6),
+                Interval::new(8, 10)
+            ]
+        );
+        assert_eq!(
+            set.complement(Interval::new(3, 7))
+                .iter()
+                .copied()
+                .collect::<Vec<_>>(),
+            vec![Interval::new(4, 6)]
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn prop_from_iter(a in intervals()) {
+            let set = set(&a);
+
+            assert_normalized(&set);
+            prop_assert_eq!(points(&set), brute(&a));
+            prop_assert_eq!(set.size(), brute(&a).len());
+        }
+
+        #[test]
+        fn prop_insert(a in intervals()) {
+            let mut set = IntervalSet::new();
+            for &i in a.iter() {
+                set.insert(i);
+            }
+
+            assert_normalized(&set);
+            prop_assert_eq!(&set, &self::set(&a));
+        }
+
+        #[test]
+        fn prop_contains(a in intervals(), n in 0..UNIVERSE) {
+            let set = set(&a);
+
+            prop_assert_eq!(set.contains(n), brute(&a).contains(&n));
+        }
+
+        #[test]
+        fn prop_union(a in intervals(), b in intervals()) {
+            let (x, y) = (set(&a), set(&b));
+            let union = x.union(&y);
+
+            assert_normalized(&union);
+            let expected = brute(&a).union(&brute(&b)).copied().collect::<BTreeSet<_>>();
+            prop_assert_eq!(points(&union), expected);
+        }
+
+        #[test]
+        fn prop_intersection(a in intervals(), b in intervals()) {
+            let (x, y) = (set(&a), set(&b));
+            let intersection = x.intersection(&y);
+
+            assert_normalized(&intersection);
+            let expected = brute(&a).intersection(&brute(&b)).copied().collect::<BTreeSet<_>>();
+            prop_assert_eq!(points(&intersection), expected);
+        }
+
+        #[test]
+        fn prop_difference(a in intervals(), b in intervals()) {
+            let (x, y) = (set(&a), set(&b));
+            let difference = x.difference(&y);
+
+            assert_normalized(&difference);
+            let expected = brute(&a).difference(&brute(&b)).copied().collect::<BTreeSet<_>>();
+            prop_assert_eq!(points(&difference), expected);
+        }
+
+        #[test]
+        fn prop_complement(a in intervals(), s in 0..UNIVERSE, l in 0..UNIVERSE) {
+            let set = set(&a);
+            let bounds = Interval::new(s, (s + l).min(UNIVERSE));
+            let complement = set.complement(bounds);
+
+            assert_normalized(&complement);
+            let expected = (bounds.start..bounds.end)
+                .filter(|n| !brute(&a).contains(n))
+                .collect::<BTreeSet<_>>();
+            prop_assert_eq!(points(&complement), expected);
+        }
+    }
+}
@@ -1,13 +1,18 @@
-use aoc_runner_derive::aoc_lib;
-
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
-
-aoc_lib! { year = 2023 }
+pub mod answers;
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod error;
+pub mod gen;
+pub mod grid;
+pub mod input;
+pub mod interval;
+mod par;
+pub mod solutions;
+pub mod stream;
@@ -1,3 +1,313 @@
-use aoc_runner_derive::aoc_main;
+use advent2023::answers::{Answers, Status};
+use advent2023::input;
+use advent2023::solutions::{solutions, Output, Solution, YEAR};
+use clap::{Args, Parser, Subcommand};
+use std::error::Error;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+use std::{fs, io};
 
-aoc_main! { lib = advent2023 }
+#[derive(Parser)]
+#[command(about = "Advent of Code 2023 solutions")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the answers of the selected solutions.
+    Run(Selection),
+    /// Compare the answers of the selected solutions with the recorded ones.
+    Check {
+        #[command(flatten)]
+        selection: Selection,
+
+        /// File holding the recorded answers. Defaults to answers/2023.toml.
+        #[arg(long)]
+        answers: Option<PathBuf>,
+
+        /// Record the answers of solutions that have none recorded yet.
+        #[arg(long)]
+        record: bool,
+    },
+    /// Time the generator and solver of the selected solutions.
+    Bench {
+        #[command(flatten)]
+        selection: Selection,
+
+        /// How many times to run every solution.
+        #[arg(long, default_value_t = 10)]
+        iterations: u32,
+    },
+}
+
+#[derive(Args)]
+struct Selection {
+    /// Days to run, e.g. `5`, `1-9` or `1,3,5-7`. Defaults to every day.
+    #[arg(long, short, value_parser = parse_days)]
+    day: Option<Days>,
+
+    /// Part to run. Defaults to both.
+    #[arg(long, short, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// Read the puzzle input from this file, or `-` for stdin, instead of input/2023/dayN.txt.
+    /// Missing inputs are downloaded there when AOC_SESSION holds a session cookie.
+    #[arg(long, short)]
+    input: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+struct Days(Vec<u8>);
+
+fn parse_days(s: &str) -> Result<Days, String> {
+    let mut days = vec![];
+
+    for item in s.split(',') {
+        let (from, to) = item.split_once('-').unwrap_or((item, item));
+        let parse = |n: &str| {
+            n.trim()
+                .parse::<u8>()
+                .map_err(|_| format!("invalid day `{}`", n))
+        };
+
+        let (from, to) = (parse(from)?, parse(to)?);
+        if from > to {
+            return Err(format!("invalid day range `{}`", item));
+        }
+
+        days.extend(from..=to);
+    }
+
+    Ok(Days(days))
+}
+
+/// Input is the text a solution runs on, or why it could not be loaded.
+type Input = Result<String, String>;
+
+impl Selection {
+    fn solutions(&self) -> Vec<&'static Solution> {
+        solutions()
+            .iter()
+            .filter(|s| self.day.as_ref().is_none_or(|d| d.0.contains(&s.day)))
+            .filter(|s| self.part.is_none_or(|p| p == s.part))
+            .collect()
+    }
+
+    /// inputs pairs every selected solution with the text it should run on. A day whose input
+    /// cannot be loaded only fails its own solutions, so the others still run.
+    fn inputs(&self) -> Result<Vec<(&'static Solution, Input)>, Box<dyn Error>> {
+        let selected = self.solutions();
+        if selected.is_empty() {
+            return Err("no solution matches the selection".into());
+        }
+
+        let Some(path) = &self.input else {
+            let provider = input::from_env("input");
+
+            return Ok(selected
+                .into_iter()
+                .map(|s| (s, provider.input(YEAR, s.day).map_err(|e| e.to_string())))
+                .collect());
+        };
+
+        if selected.iter().any(|s| s.day != selected[0].day) {
+            return Err("--input needs the selection to cover a single day".into());
+        }
+
+        let input = if path == "-" {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+            input
+        } else {
+            fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?
+        };
+
+        Ok(selected
+            .into_iter()
+            .map(|s| (s, Ok(input.clone())))
+            .collect())
+    }
+}
+
+/// failed reports why `solution` has no answer, so that the others can still run.
+fn failed(solution: &Solution, e: &dyn Error) {
+    eprintln!("Day {} - Part {}: {}", solution.day, solution.part, e);
+    eprintln!();
+}
+
+fn run(selection: &Selection) -> Result<bool, Box<dyn Error>> {
+    let mut ok = true;
+
+    for (solution, input) in selection.inputs()? {
+        let output = input
+            .map_err(Box::<dyn Error>::from)
+            .and_then(|input| solution.run(&input));
+        let Output {
+            answer,
+            generator,
+            runner,
+        } = match output {
+            Ok(output) => output,
+            Err(e) => {
+                failed(solution, e.as_ref());
+                ok = false;
+                continue;
+            }
+        };
+
+        println!("Day {} - Part {}: {}", solution.day, solution.part, answer);
+        println!("\tgenerator: {:?},", generator);
+        println!("\trunner: {:?}", runner);
+        println!();
+    }
+
+    Ok(ok)
+}
+
+fn check(selection: &Selection, path: &Path, record: bool) -> Result<bool, Box<dyn Error>> {
+    let mut answers = Answers::load(path)?;
+    let mut rows = vec![];
+    let mut recorded = 0;
+
+    for (solution, input) in selection.inputs()? {
+        let (day, part) = (solution.day, solution.part);
+
+        let (status, answer) = match input.map(|input| solution.run(&input)) {
+            Ok(Ok(Output { answer, .. })) => (answers.check(day, part, &answer), answer),
+            Ok(Err(e)) => (Status::Error(e.to_string()), String::new()),
+            Err(e) => (Status::NoInput(e), String::new()),
+        };
+
+        if record && status == Status::Missing {
+            answers.insert(day, part, answer.clone());
+            recorded += 1;
+        }
+
+        rows.push((day, part, status, answer));
+    }
+
+    let header = ["Day", "Part", "Status", "Answer", "Expected"].map(String::from);
+    let mut table = vec![header];
+    for (day, part, status, answer) in rows.iter() {
+        let expected = match status {
+            Status::Pass => answer.clone(),
+            Status::Fail { expected } => expected.clone(),
+            Status::Missing | Status::Error(_) | Status::NoInput(_) => "-".to_string(),
+        };
+
+        table.push([
+            day.to_string(),
+            part.to_string(),
+            status.to_string(),
+            answer.clone(),
+            expected,
+        ]);
+    }
+
+    let widths = (0..5)
+        .map(|c| table.iter().map(|row| row[c].len()).max().unwrap_or(0))
+        .collect::<Vec<_>>();
+    for row in table.iter() {
+        let cells = row
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, &width)| format!("{:<width$}", cell))
+            .collect::<Vec<_>>();
+
+        println!("{}", cells.join("  ").trim_end());
+    }
+
+    for (day, part, status, _) in rows.iter() {
+        if let Status::Error(e) | Status::NoInput(e) = status {
+            println!();
+            println!("Day {} - Part {}:", day, part);
+            println!("{}", e);
+        }
+    }
+
+    if recorded > 0 {
+        answers.save(path)?;
+        println!();
+        println!("recorded {} new answer(s) in {}", recorded, path.display());
+    }
+
+    // A missing input fails the check too, or a machine without inputs would pass it.
+    let ok = rows
+        .iter()
+        .all(|(_, _, status, _)| matches!(status, Status::Pass | Status::Missing));
+
+    Ok(ok)
+}
+
+fn bench(selection: &Selection, iterations: u32) -> Result<bool, Box<dyn Error>> {
+    let iterations = iterations.max(1);
+    let mut ok = true;
+
+    for (solution, input) in selection.inputs()? {
+        let outputs = input.map_err(Box::<dyn Error>::from).and_then(|input| {
+            (0..iterations)
+                .map(|_| solution.run(&input))
+                .collect::<Result<Vec<_>, _>>()
+        });
+        let outputs = match outputs {
+            Ok(outputs) => outputs,
+            Err(e) => {
+                failed(solution, e.as_ref());
+                ok = false;
+                continue;
+            }
+        };
+
+        let mean =
+            |f: fn(&Output) -> Duration| outputs.iter().map(f).sum::<Duration>() / iterations;
+        let min = |f: fn(&Output) -> Duration| outputs.iter().map(f).min().unwrap_or_default();
+
+        println!("Day {} - Part {}:", solution.day, solution.part);
+        println!(
+            "\tgenerator: mean {:?}, min {:?}",
+            mean(|o| o.generator),
+            min(|o| o.generator)
+        );
+        println!(
+            "\trunner: mean {:?}, min {:?}",
+            mean(|o| o.runner),
+            min(|o| o.runner)
+        );
+        println!();
+    }
+
+    Ok(ok)
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let result = match &cli.command {
+        Command::Run(selection) => run(selection),
+        Command::Check {
+            selection,
+            answers,
+            record,
+        } => {
+            let path = answers.clone().unwrap_or_else(|| Answers::path(YEAR));
+            check(selection, &path, *record)
+        }
+        Command::Bench {
+            selection,
+            iterations,
+        } => bench(selection, *iterations),
+    };
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
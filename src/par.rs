@@ -0,0 +1,39 @@
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// map applies `f` to every item on every core, keeping the items' order so the answers do not
+/// depend on how the work was split.
+#[cfg(feature = "parallel")]
+pub fn map<T, U, F>(items: &[T], f: F) -> Vec<U>
+where
+    T: Sync,
+    U: Send,
+    F: Fn(&T) -> U + Sync + Send,
+{
+    items.par_iter().map(f).collect()
+}
+
+/// map applies `f` to every item; build with the `parallel` feature to spread the work over
+/// every core.
+#[cfg(not(feature = "parallel"))]
+pub fn map<T, U, F>(items: &[T], f: F) -> Vec<U>
+where
+    F: Fn(&T) -> U,
+{
+    items.iter().map(f).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_keeps_order() {
+        let items = (0..10_000).collect::<Vec<usize>>();
+
+        assert_eq!(
+            map(&items, |n| n * 2),
+            items.iter().map(|n| n * 2).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use crate::{day01, day02, day03, day04, day05, day06, day07, day08, day09};
+use num_bigint::BigInt;
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+pub const YEAR: u16 = 2023;
+
+/// IntoAnswer turns whatever a solver returns into a printable answer.
+pub trait IntoAnswer {
+    fn into_answer(self) -> Result<String, Box<dyn Error>>;
+}
+
+impl IntoAnswer for usize {
+    fn into_answer(self) -> Result<String, Box<dyn Error>> {
+        Ok(self.to_string())
+    }
+}
+
+impl IntoAnswer for isize {
+    fn into_answer(self) -> Result<String, Box<dyn Error>> {
+        Ok(self.to_string())
+    }
+}
+
+impl IntoAnswer for BigInt {
+    fn into_answer(self) -> Result<String, Box<dyn Error>> {
+        Ok(self.to_string())
+    }
+}
+
+impl<T: IntoAnswer> IntoAnswer for Option<T> {
+    fn into_answer(self) -> Result<String, Box<dyn Error>> {
+        self.ok_or("the puzzle has no answer for this input")?
+            .into_answer()
+    }
+}
+
+impl<T: IntoAnswer, E: Error + 'static> IntoAnswer for Result<T, E> {
+    fn into_answer(self) -> Result<String, Box<dyn Error>> {
+        self?.into_answer()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Output {
+    pub answer: String,
+    pub generator: Duration,
+    pub runner: Duration,
+}
+
+/// Solution is one registered part of one day, wired from its generator to its solver.
+pub struct Solution {
+    pub day: u8,
+    pub part: u8,
+    run: fn(&str) -> Result<Output, Box<dyn Error>>,
+}
+
+impl Solution {
+    /// run solves the puzzle for `input`, ignoring trailing line endings, CRLF ones included.
+    pub fn run(&self, input: &str) -> Result<Output, Box<dyn Error>> {
+        (self.run)(input.trim_end_matches(['\r', '\n']))
+    }
+}
+
+macro_rules! solution {
+    ($day:literal, $part:literal, $solver:path) => {
+        Solution {
+            day: $day,
+            part: $part,
+            run: |input| {
+                let start = Instant::now();
+                let answer = $solver(input).into_answer()?;
+
+                Ok(Output {
+                    answer,
+                    generator: Duration::ZERO,
+                    runner: start.elapsed(),
+                })
+            },
+        }
+    };
+    ($day:literal, $part:literal, $generator:path => $solver:path) => {
+        Solution {
+            day: $day,
+            part: $part,
+            run: |input| {
+                let start = Instant::now();
+                let parsed = $generator(input)?;
+                let generator = start.elapsed();
+
+                let start = Instant::now();
+                let answer = $solver(&parsed).into_answer()?;
+
+                Ok(Output {
+                    answer,
+                    generator,
+                    runner: start.elapsed(),
+                })
+            },
+        }
+    };
+}
+
+/// SOLUTIONS is the one place days are registered, each part with its generator and solver.
+static SOLUTIONS: [Solution; 18] = [
+    solution!(1, 1, day01::day1_part1_puzzle),
+    solution!(1, 2, day01::day1_part2_puzzle),
+    solution!(2, 1, day02::parse_day2 => day02::day2_part1),
+    solution!(2, 2, day02::parse_day2 => day02::day2_part2),
+    solution!(3, 1, day03::parse_day3 => day03::day3_part1),
+    solution!(3, 2, day03::parse_day3 => day03::day3_part2),
+    solution!(4, 1, day04::parse_day4 => day04::day4_part1),
+    solution!(4, 2, day04::parse_day4 => day04::day4_part2),
+    solution!(5, 1, day05::parse_day5 => day05::day5_part1),
+    solution!(5, 2, day05::parse_day5 => day05::day5_part2),
+    solution!(6, 1, day06::parse_day6 => day06::day6_part1),
+    solution!(6, 2, day06::parse_day6_part2 => day06::day6_part2),
+    solution!(7, 1, day07::parse_day7 => day07::day7_part1),
+    solution!(7, 2, day07::parse_day7 => day07::day7_part2),
+    solution!(8, 1, day08::parse_day8 => day08::day8_part1),
+    solution!(8, 2, day08::parse_day8 => day08::day8_part2),
+    solution!(9, 1, day09::parse_day9 => day09::day9_part1),
+    solution!(9, 2, day09::parse_day9 => day09::day9_part2),
+];
+
+/// solutions returns every registered solution, ordered by day and then part.
+pub fn solutions() -> &'static [Solution] {
+    &SOLUTIONS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn test_run() {
+        let day1 = &solutions()[0];
+        let output = day1.run("1abc2\npqr3stu8vwx\n\n").unwrap();
+
+        assert_eq!(output.answer, "50");
+    }
+
+    #[test]
+    fn test_run_crlf() {
+        let input = [
+            "467..114..",
+            "...*......",
+            "..35..633.",
+            "......#...",
+            "617*......",
+            ".....+.58.",
+            "..592.....",
+            "......755.",
+            "...$.*....",
+            ".664.598..",
+            "",
+        ]
+        .join("\r\n");
+        let day3 = solutions().iter().find(|s| (s.day, s.part) == (3, 1));
+
+        assert_eq!(day3.unwrap().run(&input).unwrap().answer, "4361");
+    }
+
+    #[test]
+    fn test_run_reports_errors() {
+        let day2 = &solutions()[2];
+        let err = day2.run("Game 1: 3; 4 red").unwrap_err();
+
+        assert!(err.to_string().starts_with("day 2: parse error at line 1"));
+    }
+
+    /// test_fuzz_corpus replays every input kept by the fuzz targets, including the crashes they
+    /// found, none of which may panic.
+    #[test]
+    fn test_fuzz_corpus() {
+        let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz/corpus");
+
+        for solution in solutions() {
+            let dir = corpus.join(format!("day{}", solution.day));
+
+            for entry in fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                // The targets only ever see text, so bytes that do not decode were never run.
+                let input = fs::read_to_string(&path)
+                    .unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+
+                let _ = solution.run(&input);
+            }
+        }
+    }
+
+    #[test]
+    fn test_solutions_are_ordered() {
+        let keys = solutions()
+            .iter()
+            .map(|s| (s.day, s.part))
+            .collect::<Vec<_>>();
+
+        let mut sorted = keys.clone();
+        sorted.sort();
+        sorted.dedup();
+
+        assert_eq!(keys, sorted);
+    }
+
+    #[test]
+    fn test_solutions_cover_every_part() {
+        let keys = solutions()
+            .iter()
+            .map(|s| (s.day, s.part))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            keys,
+            (1..=9)
+                .flat_map(|day| [(day, 1), (day, 2)])
+                .collect::<Vec<_>>()
+        );
+    }
+}
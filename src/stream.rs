@@ -0,0 +1,334 @@
+use crate::error::{Overflow, ParseError};
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead};
+
+/// StreamError is whatever stops a streaming solver part way through its input.
+#[derive(Debug)]
+pub enum StreamError {
+    Io(io::Error),
+    Parse(ParseError),
+    /// A line that parsed but cannot be solved, such as a day01 line without digits.
+    Solve(Box<dyn Error + Send + Sync>),
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamError::Io(e) => write!(f, "{}", e),
+            StreamError::Parse(e) => write!(f, "{}", e),
+            StreamError::Solve(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for StreamError {}
+
+impl From<io::Error> for StreamError {
+    fn from(e: io::Error) -> Self {
+        StreamError::Io(e)
+    }
+}
+
+impl From<ParseError> for StreamError {
+    fn from(e: ParseError) -> Self {
+        StreamError::Parse(e)
+    }
+}
+
+impl From<Overflow> for StreamError {
+    fn from(e: Overflow) -> Self {
+        StreamError::Solve(Box::new(e))
+    }
+}
+
+/// for_each_line feeds `f` every line of `input` along with its number, counting from 1. Lines
+/// are read into one reused buffer, so memory stays flat however long the input is. Trailing
+/// blank lines are skipped, the same way the runner trims trailing newlines.
+pub fn for_each_line<F>(mut input: impl BufRead, mut f: F) -> Result<(), StreamError>
+where
+    F: FnMut(usize, &str) -> Result<(), StreamError>,
+{
+    let mut buffer = String::new();
+    let mut number = 0;
+    // Blank lines are held back until it is clear they are not trailing.
+    let mut blanks = 0;
+
+    loop {
+        buffer.clear();
+        if input.read_line(&mut buffer)? == 0 {
+            return Ok(());
+        }
+        number += 1;
+
+        let line = buffer.strip_suffix('\n').unwrap_or(&buffer);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if line.is_empty() {
+            blanks += 1;
+            continue;
+        }
+
+        for blank in number - blanks..number {
+            f(blank, "")?;
+        }
+        blanks = 0;
+
+        f(number, line)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day01::Digitless;
+    use crate::day07::RuleSet;
+    use crate::gen::Generator;
+    use crate::{day01, day02, day04, day07, day09};
+    use proptest::prelude::*;
+    use std::collections::BTreeMap;
+    use std::io::{BufReader, Read};
+
+    /// Chunks reads every chunk of `chunks` back to back, each on its own lines, without ever
+    /// holding more than one.
+    struct Chunks<I> {
+        chunks: I,
+        chunk: Vec<u8>,
+        at: usize,
+    }
+
+    impl<I: Iterator<Item = String>> Read for Chunks<I> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.at == self.chunk.len() {
+                let Some(chunk) = self.chunks.next() else {
+                    return Ok(0);
+                };
+                self.chunk = format!("{}\n", chunk).into_bytes();
+                self.at = 0;
+            }
+
+            let n = buf.len().min(self.chunk.len() - self.at);
+            buf[..n].copy_from_slice(&self.chunk[self.at..self.at + n]);
+            self.at += n;
+            Ok(n)
+        }
+    }
+
+    /// generated yields inputs for `day`, each from its own seed, until there are about `bytes`.
+    /// The same day and size always yield the same inputs.
+    fn generated(day: u8, bytes: usize) -> impl Iterator<Item = String> {
+        let mut total = 0;
+
+        (0..)
+            .map(move |seed| Generator::new(seed).with_size(1000).input(day).unwrap())
+            .take_while(move |chunk| {
+                let more = total < bytes;
+                total += chunk.len() + 1;
+                more
+            })
+    }
+
+    /// reader streams what generated yields.
+    fn reader(day: u8, bytes: usize) -> impl BufRead {
+        BufReader::new(Chunks {
+            chunks: generated(day, bytes),
+            chunk: vec![],
+            at: 0,
+        })
+    }
+
+    /// Group is how many hands sort equal, their bids, and each bid times the hands before it
+    /// in the group, the way winnings_stream counts them.
+    #[derive(Default, Clone, Copy)]
+    struct Group {
+        count: u128,
+        bids: u128,
+        weighted: u128,
+    }
+
+    impl Group {
+        /// then is this group followed by `next`, whose hands all come later in the input.
+        /// Following a group by k - 1 copies of itself gives k·c hands, k·b bids and
+        /// k·w + b·c·k(k−1)/2 weighted.
+        fn then(self, next: Group) -> Group {
+            Group {
+                count: self.count + next.count,
+                bids: self.bids + next.bids,
+                weighted: self.weighted + next.weighted + next.bids * self.count,
+            }
+        }
+    }
+
+    /// groups sums up the hands of one chunk by how they sort under `rules`.
+    fn groups(hands: &[day07::Hand], rules: &RuleSet) -> BTreeMap<impl Ord, Group> {
+        let mut groups = BTreeMap::new();
+        for hand in hands {
+            let bid = hand.bid as u128;
+            let g: &mut Group = groups.entry(rules.sort_key(hand)).or_default();
+            *g = g.then(Group {
+                count: 1,
+                bids: bid,
+                weighted: 0,
+            });
+        }
+
+        groups
+    }
+
+    /// winnings ranks the groups from 1 up, or is None past usize::MAX.
+    fn winnings<K>(groups: &BTreeMap<K, Group>) -> Option<usize> {
+        let mut base = 0;
+        let total = groups.values().fold(0, |acc, g| {
+            let total = acc + (base + 1) * g.bids + g.weighted;
+            base += g.count;
+            total
+        });
+
+        usize::try_from(total).ok()
+    }
+
+    fn lines(input: &str) -> Vec<(usize, String)> {
+        let mut lines = vec![];
+        for_each_line(input.as_bytes(), |n, l| {
+            lines.push((n, l.to_string()));
+            Ok(())
+        })
+        .unwrap();
+
+        lines
+    }
+
+    #[test]
+    fn test_for_each_line() {
+        assert_eq!(
+            lines("a\n\nb\r\nc\n\n\n"),
+            vec![
+                (1, "a".to_string()),
+                (2, "".to_string()),
+                (3, "b".to_string()),
+                (4, "c".to_string())
+            ]
+        );
+        assert_eq!(lines(""), vec![]);
+    }
+
+    #[test]
+    fn test_for_each_line_stops_at_first_error() {
+        let mut seen = 0;
+        let err = for_each_line("1\n2\n3".as_bytes(), |n, _| {
+            seen += 1;
+            match n {
+                2 => Err(StreamError::Solve("no".into())),
+                _ => Ok(()),
+            }
+        })
+        .unwrap_err();
+
+        assert_eq!(seen, 2);
+        assert_eq!(err.to_string(), "no");
+    }
+
+    proptest! {
+        #[test]
+        fn prop_streams_match(seed in any::<u64>(), size in 1..40_usize) {
+            let input = |day| Generator::new(seed).with_size(size).input(day).unwrap();
+
+            let english = day01::Vocabulary::english();
+            let i = input(1);
+            prop_assert_eq!(day01::day1_part1_stream(i.as_bytes(), Digitless::Error).unwrap(), day01::day1_part1(&i, Digitless::Error).unwrap());
+            prop_assert_eq!(day01::day1_part2_stream(i.as_bytes(), &english, Digitless::Error).unwrap(), day01::day1_part2(&i, &english, Digitless::Error).unwrap());
+
+            let i = input(2);
+            let games = day02::parse_day2(&i).unwrap();
+            prop_assert_eq!(day02::day2_part1_stream(i.as_bytes()).unwrap(), day02::day2_part1(&games).unwrap());
+            prop_assert_eq!(day02::day2_part2_stream(i.as_bytes()).unwrap(), day02::day2_part2(&games).unwrap());
+
+            let i = input(4);
+            let cards = day04::parse_day4(&i).unwrap();
+            prop_assert_eq!(day04::day4_part1_stream(i.as_bytes()).unwrap(), day04::day4_part1(&cards).unwrap());
+            prop_assert_eq!(day04::day4_part2_stream(i.as_bytes()).unwrap(), day04::day4_part2(&cards).unwrap());
+
+            let i = input(7);
+            let hands = day07::parse_day7(&i).unwrap();
+            prop_assert_eq!(day07::day7_part1_stream(i.as_bytes()).unwrap(), day07::day7_part1(&hands).unwrap());
+            prop_assert_eq!(day07::day7_part2_stream(i.as_bytes()).unwrap(), day07::day7_part2(&hands).unwrap());
+
+            let i = input(9);
+            let report = day09::parse_day9(&i).unwrap();
+            prop_assert_eq!(day09::day9_part1_stream(i.as_bytes()).unwrap(), day09::day9_part1(&report).unwrap());
+            prop_assert_eq!(day09::day9_part2_stream(i.as_bytes()).unwrap(), day09::day9_part2(&report).unwrap());
+        }
+    }
+
+    /// test_streams_scale runs every stream over a few gigabytes of generated input, which the
+    /// whole-input solvers could not hold. The chunks are independent, so each answer is the
+    /// sum of the whole-input answers of the chunks, except for day07, whose ranks run across
+    /// chunks and are put back together from each chunk's groups. Run it with
+    /// `cargo test --release -- --ignored`.
+    #[test]
+    #[ignore]
+    fn test_streams_scale() {
+        const BYTES: usize = 2 << 30;
+
+        let english = day01::Vocabulary::english();
+        let (mut part1, mut part2) = (0, 0);
+        for chunk in generated(1, BYTES) {
+            part1 += day01::day1_part1(&chunk, Digitless::Error).unwrap();
+            part2 += day01::day1_part2(&chunk, &english, Digitless::Error).unwrap();
+        }
+        assert_eq!(
+            day01::day1_part1_stream(reader(1, BYTES), Digitless::Error).unwrap(),
+            part1
+        );
+        assert_eq!(
+            day01::day1_part2_stream(reader(1, BYTES), &english, Digitless::Error).unwrap(),
+            part2
+        );
+
+        let (mut part1, mut part2) = (0, 0);
+        for chunk in generated(2, BYTES) {
+            let games = day02::parse_day2(&chunk).unwrap();
+            part1 += day02::day2_part1(&games).unwrap();
+            part2 += day02::day2_part2(&games).unwrap();
+        }
+        assert_eq!(day02::day2_part1_stream(reader(2, BYTES)).unwrap(), part1);
+        assert_eq!(day02::day2_part2_stream(reader(2, BYTES)).unwrap(), part2);
+
+        let (mut part1, mut part2) = (0, 0);
+        for chunk in generated(4, BYTES) {
+            let cards = day04::parse_day4(&chunk).unwrap();
+            part1 += day04::day4_part1(&cards).unwrap();
+            part2 += day04::day4_part2(&cards).unwrap();
+        }
+        assert_eq!(day04::day4_part1_stream(reader(4, BYTES)).unwrap(), part1);
+        assert_eq!(day04::day4_part2_stream(reader(4, BYTES)).unwrap(), part2);
+
+        let rules = [RuleSet::camel_cards(), RuleSet::jokers()];
+        let mut totals = [BTreeMap::new(), BTreeMap::new()];
+        for (i, chunk) in generated(7, BYTES).enumerate() {
+            let hands = day07::parse_day7(&chunk).unwrap();
+            for (rules, total) in rules.iter().zip(&mut totals) {
+                let groups = groups(&hands, rules);
+                // Sorting every chunk would take longer than streaming it.
+                if i == 0 {
+                    assert_eq!(winnings(&groups), day07::winnings(&hands, rules).ok());
+                }
+                for (key, g) in groups {
+                    let t: &mut Group = total.entry(key).or_default();
+                    *t = t.then(g);
+                }
+            }
+        }
+        let [part1, part2] = totals.map(|t| winnings(&t));
+        assert_eq!(day07::day7_part1_stream(reader(7, BYTES)).ok(), part1);
+        assert_eq!(day07::day7_part2_stream(reader(7, BYTES)).ok(), part2);
+
+        let (mut part1, mut part2) = (0, 0);
+        for chunk in generated(9, BYTES) {
+            let report = day09::parse_day9(&chunk).unwrap();
+            part1 += day09::day9_part1(&report).unwrap();
+            part2 += day09::day9_part2(&report).unwrap();
+        }
+        assert_eq!(day09::day9_part1_stream(reader(9, BYTES)).unwrap(), part1);
+        assert_eq!(day09::day9_part2_stream(reader(9, BYTES)).unwrap(), part2);
+    }
+}